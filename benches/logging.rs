@@ -0,0 +1,204 @@
+//! Benchmarks for the per-record formatting path.
+//!
+//! The global logger can only be installed once per process, so every
+//! benchmark here shares one fully featured configuration: coloured output
+//! with caller information and level padding, written to the null device.
+//!
+//! The `baseline` group renders the same records the way twyg did before
+//! records were rendered into a reused line buffer: every coloured part
+//! formatted into its own `String` with `format!`, key-value pairs collected
+//! and joined, then written piecewise while holding the output lock. Compare
+//! the two groups' reports for the same benchmark name, e.g.
+//! `cargo bench --bench logging -- key_values` runs `write_log/key_values` and
+//! `baseline/key_values` back to back.
+//!
+//! Run with `cargo bench`; criterion also reports the change against the
+//! previous run.
+
+use std::fmt;
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{self, BufWriter, Write};
+use std::sync::Mutex;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use log::kv::{Key, Source, Value, VisitSource};
+use log::{debug, info, Level, Record};
+use twyg::{LogLevel, OptsBuilder, Output};
+
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+#[cfg(not(windows))]
+const NULL_DEVICE: &str = "/dev/null";
+
+fn setup() {
+    let opts = OptsBuilder::new()
+        .coloured(true)
+        .file_coloured(true)
+        .output(Output::file(NULL_DEVICE))
+        .level(LogLevel::Info)
+        .report_caller(true)
+        .pad_level(true)
+        .build()
+        .unwrap();
+    twyg::setup(opts).unwrap();
+}
+
+fn bench_records(c: &mut Criterion) {
+    setup();
+    let mut group = c.benchmark_group("write_log");
+
+    group.bench_function("message", |b| b.iter(|| info!("request handled")));
+
+    group.bench_function("formatted_message", |b| {
+        b.iter(|| {
+            info!(
+                "user {} logged in from {}",
+                black_box("alice"),
+                black_box("10.0.0.1")
+            )
+        })
+    });
+
+    group.bench_function("key_values", |b| {
+        b.iter(|| {
+            info!(
+                user = black_box("alice"),
+                status = black_box(200),
+                elapsed_ms = black_box(12.5),
+                cached = black_box(true);
+                "request handled"
+            )
+        })
+    });
+
+    group.bench_function("filtered", |b| b.iter(|| debug!("not written")));
+
+    group.finish();
+}
+
+/// The pre-line-buffer formatter, kept here as a fixed point of comparison.
+struct Baseline {
+    output: Mutex<BufWriter<File>>,
+}
+
+impl Baseline {
+    fn new() -> Self {
+        Baseline {
+            output: Mutex::new(BufWriter::new(File::create(NULL_DEVICE).unwrap())),
+        }
+    }
+
+    fn paint(code: &str, text: &str) -> String {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    }
+
+    fn write_log(&self, record: &Record) -> io::Result<()> {
+        let mut writer = self.output.lock().unwrap();
+        let timestamp = Self::paint(
+            "32",
+            &chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
+        let level = Self::paint("34", &format!("{:<5}", record.level()));
+        let caller = Self::paint(
+            "33",
+            &format!(
+                "{}:{}",
+                record.file().unwrap_or("??"),
+                record.line().unwrap_or(0)
+            ),
+        );
+        let target = Self::paint("1;33", record.target());
+        let arrow = Self::paint("36", "▶");
+        let message = Self::paint("1;32", &record.args().to_string());
+
+        let mut pairs = PairCollector(Vec::new());
+        let _ = record.key_values().visit(&mut pairs);
+        let fields = if pairs.0.is_empty() {
+            String::new()
+        } else {
+            let joined = pairs
+                .0
+                .iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{}={}",
+                        Self::paint("33", k),
+                        Self::paint("1;33", &format!("{{{}}}", v))
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(": {}", joined)
+        };
+
+        write!(
+            writer,
+            "{} {} [{} {}] {} {}{}",
+            timestamp, level, caller, target, arrow, message, fields
+        )?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+struct PairCollector(Vec<(String, String)>);
+
+impl<'kvs> VisitSource<'kvs> for PairCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+fn record<'a>(args: fmt::Arguments<'a>, kvs: &'a dyn Source) -> Record<'a> {
+    Record::builder()
+        .level(Level::Info)
+        .target(module_path!())
+        .module_path(Some(module_path!()))
+        .file(Some(file!()))
+        .line(Some(line!()))
+        .args(args)
+        .key_values(kvs)
+        .build()
+}
+
+fn bench_baseline(c: &mut Criterion) {
+    let baseline = Baseline::new();
+    let mut group = c.benchmark_group("baseline");
+    let none: &[(&str, &str)] = &[];
+
+    group.bench_function("message", |b| {
+        b.iter(|| baseline.write_log(&record(format_args!("request handled"), &none)))
+    });
+
+    group.bench_function("formatted_message", |b| {
+        b.iter(|| {
+            baseline.write_log(&record(
+                format_args!(
+                    "user {} logged in from {}",
+                    black_box("alice"),
+                    black_box("10.0.0.1")
+                ),
+                &none,
+            ))
+        })
+    });
+
+    group.bench_function("key_values", |b| {
+        b.iter(|| {
+            let kvs = [
+                ("user", Value::from(black_box("alice"))),
+                ("status", Value::from(black_box(200))),
+                ("elapsed_ms", Value::from(black_box(12.5))),
+                ("cached", Value::from(black_box(true))),
+            ];
+            baseline.write_log(&record(format_args!("request handled"), &kvs))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_records, bench_baseline);
+criterion_main!(benches);
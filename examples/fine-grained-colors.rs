@@ -9,6 +9,7 @@
 
 use twyg::{Color, ColorAttribute, Colors, LogLevel, OptsBuilder, PadSide, TSFormat};
 
+#[allow(clippy::field_reassign_with_default)]
 fn main() {
     // Create a radically different color scheme with vivid, high-contrast colors
     let mut colors = Colors::default();
@@ -0,0 +1,200 @@
+//! Caller location display configuration.
+//!
+//! This module provides the [`CallerPath`] enum, which controls how the
+//! caller's location is shown when `report_caller` is enabled.
+
+use serde::{Deserialize, Serialize};
+
+/// How the caller's file is displayed.
+///
+/// rustc embeds whatever path it was given, which for dependencies is often
+/// a long `~/.cargo/registry/...` path. These presets shorten it.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{CallerPath, OptsBuilder};
+///
+/// let opts = OptsBuilder::new()
+///     .report_caller(true)
+///     .caller_path(CallerPath::LastComponents(2))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallerPath {
+    /// The path as embedded by rustc: "src/server/handler.rs"
+    #[default]
+    Full,
+
+    /// The path with a leading prefix removed, if present
+    StripPrefix(String),
+
+    /// Only the file name: "handler.rs"
+    Basename,
+
+    /// The last N path components: "server/handler.rs" for 2
+    LastComponents(usize),
+
+    /// The record's module path instead of the file: "myapp::server::handler"
+    ModulePath,
+}
+
+impl CallerPath {
+    /// Returns the caller text to display for a record's file and module path.
+    pub(crate) fn display<'a>(
+        &self,
+        file: Option<&'a str>,
+        module_path: Option<&'a str>,
+    ) -> Option<&'a str> {
+        if let Self::ModulePath = self {
+            return module_path;
+        }
+
+        let file = file?;
+        Some(match self {
+            Self::Full | Self::ModulePath => file,
+            Self::StripPrefix(prefix) => file
+                .strip_prefix(prefix.as_str())
+                .filter(|rest| prefix.ends_with(is_separator) || rest.starts_with(is_separator))
+                .map(|rest| rest.trim_start_matches(is_separator))
+                .filter(|rest| !rest.is_empty())
+                .unwrap_or(file),
+            Self::Basename => last_components(file, 1),
+            Self::LastComponents(n) => last_components(file, *n),
+        })
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+/// Returns the suffix of `path` made of its last `n` components.
+fn last_components(path: &str, n: usize) -> &str {
+    let mut start = path.len();
+    for _ in 0..n.max(1) {
+        match path[..start].rfind(is_separator) {
+            Some(i) => start = i,
+            None => return path,
+        }
+    }
+    &path[start + 1..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEP: &str =
+        "/home/alice/.cargo/registry/src/index.crates.io-6f17d22bba15001f/hyper-1.4.1/src/proto/h1/conn.rs";
+
+    #[test]
+    fn test_caller_path_default() {
+        assert_eq!(CallerPath::default(), CallerPath::Full);
+    }
+
+    #[test]
+    fn test_caller_path_full() {
+        assert_eq!(CallerPath::Full.display(Some(DEP), None), Some(DEP));
+        assert_eq!(
+            CallerPath::Full.display(Some("src/main.rs"), None),
+            Some("src/main.rs")
+        );
+    }
+
+    #[test]
+    fn test_caller_path_strip_prefix() {
+        let strip = CallerPath::StripPrefix(
+            "/home/alice/.cargo/registry/src/index.crates.io-6f17d22bba15001f".to_string(),
+        );
+        assert_eq!(
+            strip.display(Some(DEP), None),
+            Some("hyper-1.4.1/src/proto/h1/conn.rs")
+        );
+
+        // Paths without the prefix are left untouched
+        assert_eq!(
+            strip.display(Some("src/main.rs"), None),
+            Some("src/main.rs")
+        );
+
+        // A trailing separator on the prefix is optional
+        let strip = CallerPath::StripPrefix("src/".to_string());
+        assert_eq!(strip.display(Some("src/main.rs"), None), Some("main.rs"));
+        let strip = CallerPath::StripPrefix("src".to_string());
+        assert_eq!(strip.display(Some("src/main.rs"), None), Some("main.rs"));
+
+        // Only whole path components are stripped
+        assert_eq!(
+            strip.display(Some("src2/lib.rs"), None),
+            Some("src2/lib.rs")
+        );
+    }
+
+    #[test]
+    fn test_caller_path_strip_whole_path_keeps_file() {
+        let strip = CallerPath::StripPrefix("src/main.rs".to_string());
+        assert_eq!(
+            strip.display(Some("src/main.rs"), None),
+            Some("src/main.rs")
+        );
+    }
+
+    #[test]
+    fn test_caller_path_basename() {
+        assert_eq!(
+            CallerPath::Basename.display(Some(DEP), None),
+            Some("conn.rs")
+        );
+        assert_eq!(
+            CallerPath::Basename.display(Some("main.rs"), None),
+            Some("main.rs")
+        );
+        assert_eq!(
+            CallerPath::Basename.display(Some(r"src\bin\tool.rs"), None),
+            Some("tool.rs")
+        );
+    }
+
+    #[test]
+    fn test_caller_path_last_components() {
+        let last = CallerPath::LastComponents(3);
+        assert_eq!(last.display(Some(DEP), None), Some("proto/h1/conn.rs"));
+        assert_eq!(last.display(Some("src/main.rs"), None), Some("src/main.rs"));
+        assert_eq!(
+            CallerPath::LastComponents(1).display(Some(DEP), None),
+            Some("conn.rs")
+        );
+    }
+
+    #[test]
+    fn test_caller_path_module_path() {
+        assert_eq!(
+            CallerPath::ModulePath.display(Some(DEP), Some("hyper::proto::h1::conn")),
+            Some("hyper::proto::h1::conn")
+        );
+        assert_eq!(CallerPath::ModulePath.display(Some(DEP), None), None);
+    }
+
+    #[test]
+    fn test_caller_path_unknown_file() {
+        assert_eq!(CallerPath::Full.display(None, Some("app")), None);
+        assert_eq!(CallerPath::Basename.display(None, None), None);
+    }
+
+    #[test]
+    fn test_caller_path_serialize_deserialize() {
+        for path in [
+            CallerPath::Full,
+            CallerPath::StripPrefix("/src".to_string()),
+            CallerPath::Basename,
+            CallerPath::LastComponents(2),
+            CallerPath::ModulePath,
+        ] {
+            let serialized = serde_json::to_string(&path).unwrap();
+            let deserialized: CallerPath = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(path, deserialized);
+        }
+    }
+}
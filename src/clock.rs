@@ -0,0 +1,155 @@
+//! Time sources for log timestamps.
+//!
+//! This module provides the [`Clock`] trait that twyg reads the time from,
+//! with [`SystemClock`] for normal use and [`ManualClock`] for tests and
+//! snapshot suites that need to freeze or step time.
+
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// A source of wall-clock and monotonic time.
+///
+/// twyg reads all time through the clock set with
+/// [`OptsBuilder::clock`](crate::OptsBuilder::clock): wall-clock timestamps
+/// use [`Clock::now`], while elapsed and delta timestamps use
+/// [`Clock::monotonic`].
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current wall-clock time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Returns a monotonic reading: the time since an arbitrary fixed origin.
+    fn monotonic(&self) -> Duration;
+}
+
+/// The system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn monotonic(&self) -> Duration {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use chrono::{TimeZone, Utc};
+/// use twyg::{ManualClock, OptsBuilder};
+///
+/// let clock = Arc::new(ManualClock::new(
+///     Utc.with_ymd_and_hms(2026, 1, 15, 14, 30, 52).unwrap(),
+/// ));
+/// let opts = OptsBuilder::new().clock(clock.clone()).build().unwrap();
+///
+/// // Records logged from here on are stamped 2026-01-15 14:30:53
+/// clock.advance(Duration::from_secs(1));
+/// ```
+#[derive(Debug)]
+pub struct ManualClock {
+    state: Mutex<ManualState>,
+}
+
+#[derive(Debug)]
+struct ManualState {
+    now: DateTime<Utc>,
+    monotonic: Duration,
+}
+
+impl ManualClock {
+    /// Creates a clock frozen at `now`.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        ManualClock {
+            state: Mutex::new(ManualState {
+                now,
+                monotonic: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// Sets the wall-clock time, leaving the monotonic reading unchanged.
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.state().now = now;
+    }
+
+    /// Moves both the wall-clock time and the monotonic reading forward.
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.state();
+        state.now += by;
+        state.monotonic += by;
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ManualState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.state().now
+    }
+
+    fn monotonic(&self) -> Duration {
+        self.state().monotonic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 15, 14, 30, 52).unwrap()
+    }
+
+    #[test]
+    fn test_system_clock_monotonic_never_goes_back() {
+        let clock = SystemClock;
+        let first = clock.monotonic();
+        assert!(clock.monotonic() >= first);
+    }
+
+    #[test]
+    fn test_manual_clock_is_frozen() {
+        let clock = ManualClock::new(start());
+        assert_eq!(clock.now(), start());
+        assert_eq!(clock.now(), start());
+        assert_eq!(clock.monotonic(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_manual_clock_advance() {
+        let clock = ManualClock::new(start());
+        clock.advance(Duration::from_millis(1_500));
+        assert_eq!(
+            clock.now(),
+            Utc.with_ymd_and_hms(2026, 1, 15, 14, 30, 53).unwrap() + Duration::from_millis(500)
+        );
+        assert_eq!(clock.monotonic(), Duration::from_millis(1_500));
+    }
+
+    #[test]
+    fn test_manual_clock_set_keeps_monotonic() {
+        let clock = ManualClock::new(start());
+        clock.advance(Duration::from_secs(2));
+        let earlier = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        clock.set(earlier);
+        assert_eq!(clock.now(), earlier);
+        assert_eq!(clock.monotonic(), Duration::from_secs(2));
+    }
+}
@@ -3,8 +3,12 @@
 //! This module provides fine-grained control over colors for all log components,
 //! matching the zylog Go library's color system.
 
+use std::collections::BTreeMap;
+use std::fmt;
+
+use log::kv::Value;
 use log::Level;
-use owo_colors::{OwoColorize, Stream};
+use owo_colors::{AnsiColors, OwoColorize, Stream};
 use serde::{Deserialize, Serialize};
 
 /// Color attribute for terminal output.
@@ -36,86 +40,40 @@ pub enum ColorAttribute {
 }
 
 impl ColorAttribute {
-    /// Apply this color to a string using owo-colors
-    pub(crate) fn apply(&self, text: &str, stream: Stream) -> String {
+    /// The owo-colors colour for this attribute, or `None` for `Reset`
+    fn ansi(&self) -> Option<AnsiColors> {
         match self {
-            Self::Reset => text.to_string(),
-            Self::Black => text.if_supports_color(stream, |x| x.black()).to_string(),
-            Self::Red => text.if_supports_color(stream, |x| x.red()).to_string(),
-            Self::Green => text.if_supports_color(stream, |x| x.green()).to_string(),
-            Self::Yellow => text.if_supports_color(stream, |x| x.yellow()).to_string(),
-            Self::Blue => text.if_supports_color(stream, |x| x.blue()).to_string(),
-            Self::Magenta => text.if_supports_color(stream, |x| x.magenta()).to_string(),
-            Self::Cyan => text.if_supports_color(stream, |x| x.cyan()).to_string(),
-            Self::White => text.if_supports_color(stream, |x| x.white()).to_string(),
-            Self::HiBlack => text
-                .if_supports_color(stream, |x| x.bright_black())
-                .to_string(),
-            Self::HiRed => text
-                .if_supports_color(stream, |x| x.bright_red())
-                .to_string(),
-            Self::HiGreen => text
-                .if_supports_color(stream, |x| x.bright_green())
-                .to_string(),
-            Self::HiYellow => text
-                .if_supports_color(stream, |x| x.bright_yellow())
-                .to_string(),
-            Self::HiBlue => text
-                .if_supports_color(stream, |x| x.bright_blue())
-                .to_string(),
-            Self::HiMagenta => text
-                .if_supports_color(stream, |x| x.bright_magenta())
-                .to_string(),
-            Self::HiCyan => text
-                .if_supports_color(stream, |x| x.bright_cyan())
-                .to_string(),
-            Self::HiWhite => text
-                .if_supports_color(stream, |x| x.bright_white())
-                .to_string(),
+            Self::Reset => None,
+            Self::Black => Some(AnsiColors::Black),
+            Self::Red => Some(AnsiColors::Red),
+            Self::Green => Some(AnsiColors::Green),
+            Self::Yellow => Some(AnsiColors::Yellow),
+            Self::Blue => Some(AnsiColors::Blue),
+            Self::Magenta => Some(AnsiColors::Magenta),
+            Self::Cyan => Some(AnsiColors::Cyan),
+            Self::White => Some(AnsiColors::White),
+            Self::HiBlack => Some(AnsiColors::BrightBlack),
+            Self::HiRed => Some(AnsiColors::BrightRed),
+            Self::HiGreen => Some(AnsiColors::BrightGreen),
+            Self::HiYellow => Some(AnsiColors::BrightYellow),
+            Self::HiBlue => Some(AnsiColors::BrightBlue),
+            Self::HiMagenta => Some(AnsiColors::BrightMagenta),
+            Self::HiCyan => Some(AnsiColors::BrightCyan),
+            Self::HiWhite => Some(AnsiColors::BrightWhite),
         }
     }
 
+    /// Apply this color to a string using owo-colors
+    #[cfg(test)]
+    pub(crate) fn apply(&self, text: &str, stream: Stream) -> String {
+        Painted::new(text, Some(&Color::fg(*self)), Some(stream)).to_string()
+    }
+
     /// Apply as background color
+    #[cfg(test)]
     pub(crate) fn apply_bg(&self, text: &str, stream: Stream) -> String {
-        match self {
-            Self::Reset => text.to_string(),
-            Self::Black => text.if_supports_color(stream, |x| x.on_black()).to_string(),
-            Self::Red => text.if_supports_color(stream, |x| x.on_red()).to_string(),
-            Self::Green => text.if_supports_color(stream, |x| x.on_green()).to_string(),
-            Self::Yellow => text
-                .if_supports_color(stream, |x| x.on_yellow())
-                .to_string(),
-            Self::Blue => text.if_supports_color(stream, |x| x.on_blue()).to_string(),
-            Self::Magenta => text
-                .if_supports_color(stream, |x| x.on_magenta())
-                .to_string(),
-            Self::Cyan => text.if_supports_color(stream, |x| x.on_cyan()).to_string(),
-            Self::White => text.if_supports_color(stream, |x| x.on_white()).to_string(),
-            Self::HiBlack => text
-                .if_supports_color(stream, |x| x.on_bright_black())
-                .to_string(),
-            Self::HiRed => text
-                .if_supports_color(stream, |x| x.on_bright_red())
-                .to_string(),
-            Self::HiGreen => text
-                .if_supports_color(stream, |x| x.on_bright_green())
-                .to_string(),
-            Self::HiYellow => text
-                .if_supports_color(stream, |x| x.on_bright_yellow())
-                .to_string(),
-            Self::HiBlue => text
-                .if_supports_color(stream, |x| x.on_bright_blue())
-                .to_string(),
-            Self::HiMagenta => text
-                .if_supports_color(stream, |x| x.on_bright_magenta())
-                .to_string(),
-            Self::HiCyan => text
-                .if_supports_color(stream, |x| x.on_bright_cyan())
-                .to_string(),
-            Self::HiWhite => text
-                .if_supports_color(stream, |x| x.on_bright_white())
-                .to_string(),
-        }
+        let color = Color::new(ColorAttribute::Reset, *self);
+        Painted::new(text, Some(&color), Some(stream)).to_string()
     }
 }
 
@@ -153,12 +111,55 @@ impl Color {
     }
 
     /// Apply both foreground and background to text
+    #[cfg(test)]
     pub(crate) fn apply(&self, text: &str, stream: Stream) -> String {
-        let with_fg = self.fg.apply(text, stream);
-        if self.bg == ColorAttribute::Reset {
-            with_fg
-        } else {
-            self.bg.apply_bg(&with_fg, stream)
+        Painted::new(text, Some(self), Some(stream)).to_string()
+    }
+}
+
+/// Text with an optional colour, written straight to the formatter.
+///
+/// The text is left plain when there is no colour or no stream (an
+/// uncoloured sink), or when owo-colors decides the stream can't show colour.
+pub(crate) struct Painted<'a, T> {
+    text: T,
+    color: Option<&'a Color>,
+    stream: Option<Stream>,
+}
+
+impl<'a, T: fmt::Display> Painted<'a, T> {
+    pub(crate) fn new(text: T, color: Option<&'a Color>, stream: Option<Stream>) -> Self {
+        Painted {
+            text,
+            color,
+            stream,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Painted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Some(color), Some(stream)) = (self.color, self.stream) else {
+            return self.text.fmt(f);
+        };
+
+        // The background wraps the foreground, as owo-colors nests them
+        match (color.fg.ansi(), color.bg.ansi()) {
+            (None, None) => self.text.fmt(f),
+            (Some(fg), None) => self
+                .text
+                .if_supports_color(stream, |text| text.color(fg))
+                .fmt(f),
+            (None, Some(bg)) => self
+                .text
+                .if_supports_color(stream, |text| text.on_color(bg))
+                .fmt(f),
+            (Some(fg), Some(bg)) => {
+                let with_fg = self.text.if_supports_color(stream, |text| text.color(fg));
+                with_fg
+                    .if_supports_color(stream, |text| text.on_color(bg))
+                    .fmt(f)
+            }
         }
     }
 }
@@ -216,7 +217,21 @@ impl Color {
 }
 
 /// Fine-grained color configuration for all log components.
+///
+/// New settings may be added in minor releases, so start from
+/// [`Colors::default`] and change fields or chain the `with_*` setters.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{Color, Colors};
+///
+/// let colors = Colors::default()
+///     .with_message(Some(Color::hi_cyan()))
+///     .with_target_hashed(true);
+/// ```
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Colors {
     /// Timestamp color (default: Green to match current behavior)
     #[serde(default = "default_timestamp")]
@@ -262,6 +277,25 @@ pub struct Colors {
     #[serde(default = "default_target")]
     pub target: Option<Color>,
 
+    /// Colour each target from `target_palette` by hashing its name, so the
+    /// same target always gets the same colour (default: false)
+    #[serde(default)]
+    pub target_hashed: bool,
+
+    /// Palette used when `target_hashed` is set (default: six bright colours)
+    #[serde(default = "default_target_palette")]
+    pub target_palette: Vec<Color>,
+
+    /// Explicit per-target colours, taking precedence over `target` and the
+    /// palette. A key also matches its child modules: "myapp::db" colours
+    /// "myapp::db::pool" (default: empty)
+    #[serde(default)]
+    pub target_overrides: BTreeMap<String, Color>,
+
+    /// Thread name or ID (default: Magenta)
+    #[serde(default = "default_thread")]
+    pub thread: Option<Color>,
+
     /// Structured logging key (default: HiYellow)
     #[serde(default = "default_attr_key")]
     pub attr_key: Option<Color>,
@@ -269,6 +303,27 @@ pub struct Colors {
     /// Structured logging value (default: Cyan)
     #[serde(default = "default_attr_value")]
     pub attr_value: Option<Color>,
+
+    /// Numeric structured logging values, instead of `attr_value` (default: None)
+    #[serde(default)]
+    pub attr_number: Option<Color>,
+
+    /// Boolean structured logging values, instead of `attr_value` (default: None)
+    #[serde(default)]
+    pub attr_bool: Option<Color>,
+
+    /// String structured logging values, instead of `attr_value` (default: None)
+    #[serde(default)]
+    pub attr_string: Option<Color>,
+
+    /// Error structured logging values, instead of `attr_value` (default: None)
+    #[serde(default)]
+    pub attr_error: Option<Color>,
+
+    /// Per-field colours keyed by field name, applied to both the key and the
+    /// value: e.g. "error" in red (default: empty)
+    #[serde(default)]
+    pub attr_overrides: BTreeMap<String, Color>,
 }
 
 // Default value functions for serde per-field defaults
@@ -305,6 +360,19 @@ fn default_caller_line() -> Option<Color> {
 fn default_target() -> Option<Color> {
     Some(Color::hi_yellow())
 }
+fn default_target_palette() -> Vec<Color> {
+    vec![
+        Color::hi_red(),
+        Color::hi_green(),
+        Color::hi_yellow(),
+        Color::hi_blue(),
+        Color::hi_magenta(),
+        Color::hi_cyan(),
+    ]
+}
+fn default_thread() -> Option<Color> {
+    Some(Color::magenta())
+}
 fn default_attr_key() -> Option<Color> {
     Some(Color::hi_yellow())
 }
@@ -327,12 +395,61 @@ impl Default for Colors {
             caller_file: Some(Color::hi_yellow()),
             caller_line: Some(Color::hi_yellow()),
             target: Some(Color::hi_yellow()),
+            target_hashed: false,
+            target_palette: default_target_palette(),
+            target_overrides: BTreeMap::new(),
+            thread: Some(Color::magenta()),
             attr_key: Some(Color::hi_yellow()),
             attr_value: Some(Color::cyan()),
+            attr_number: None,
+            attr_bool: None,
+            attr_string: None,
+            attr_error: None,
+            attr_overrides: BTreeMap::new(),
         }
     }
 }
 
+/// Generates a builder-style setter for each field of [`Colors`].
+macro_rules! setters {
+    ($($setter:ident => $field:ident: $ty:ty,)*) => {
+        impl Colors {
+            $(
+                #[doc = concat!("Sets `", stringify!($field), "`.")]
+                pub fn $setter(mut self, value: $ty) -> Self {
+                    self.$field = value;
+                    self
+                }
+            )*
+        }
+    };
+}
+
+setters! {
+    with_timestamp => timestamp: Option<Color>,
+    with_level_trace => level_trace: Option<Color>,
+    with_level_debug => level_debug: Option<Color>,
+    with_level_info => level_info: Option<Color>,
+    with_level_warn => level_warn: Option<Color>,
+    with_level_error => level_error: Option<Color>,
+    with_message => message: Option<Color>,
+    with_arrow => arrow: Option<Color>,
+    with_caller_file => caller_file: Option<Color>,
+    with_caller_line => caller_line: Option<Color>,
+    with_target => target: Option<Color>,
+    with_target_hashed => target_hashed: bool,
+    with_target_palette => target_palette: Vec<Color>,
+    with_target_overrides => target_overrides: BTreeMap<String, Color>,
+    with_thread => thread: Option<Color>,
+    with_attr_key => attr_key: Option<Color>,
+    with_attr_value => attr_value: Option<Color>,
+    with_attr_number => attr_number: Option<Color>,
+    with_attr_bool => attr_bool: Option<Color>,
+    with_attr_string => attr_string: Option<Color>,
+    with_attr_error => attr_error: Option<Color>,
+    with_attr_overrides => attr_overrides: BTreeMap<String, Color>,
+}
+
 impl Colors {
     /// Get color for a specific log level
     pub(crate) fn level_color(&self, level: Level) -> Option<&Color> {
@@ -344,6 +461,91 @@ impl Colors {
             Level::Trace => self.level_trace.as_ref(),
         }
     }
+
+    /// Get color for a target: the most specific override, then the hashed
+    /// palette colour if enabled, then `target`
+    pub(crate) fn target_color(&self, target: &str) -> Option<&Color> {
+        let overridden = self
+            .target_overrides
+            .iter()
+            .filter(|(key, _)| is_target_or_child(target, key))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, color)| color);
+        if overridden.is_some() {
+            return overridden;
+        }
+
+        if self.target_hashed && !self.target_palette.is_empty() {
+            let index = fnv1a(target) % self.target_palette.len() as u64;
+            return self.target_palette.get(index as usize);
+        }
+
+        self.target.as_ref()
+    }
+
+    /// Get color for a structured logging key
+    pub(crate) fn attr_key_color(&self, key: &str) -> Option<&Color> {
+        self.attr_overrides.get(key).or(self.attr_key.as_ref())
+    }
+
+    /// Get color for a structured logging value: the field's override, then
+    /// the colour for its value type if set, then `attr_value`
+    pub(crate) fn attr_value_color(&self, key: &str, kind: ValueKind) -> Option<&Color> {
+        if let Some(color) = self.attr_overrides.get(key) {
+            return Some(color);
+        }
+
+        let by_kind = match kind {
+            ValueKind::Number => self.attr_number.as_ref(),
+            ValueKind::Bool => self.attr_bool.as_ref(),
+            ValueKind::String => self.attr_string.as_ref(),
+            ValueKind::Error => self.attr_error.as_ref(),
+            ValueKind::Other => None,
+        };
+        by_kind.or(self.attr_value.as_ref())
+    }
+}
+
+/// The type of a structured logging value, used to pick its colour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ValueKind {
+    Number,
+    Bool,
+    String,
+    Error,
+    Other,
+}
+
+impl ValueKind {
+    /// Classifies a captured value by its underlying type.
+    pub(crate) fn of(value: &Value) -> Self {
+        if value.to_borrowed_error().is_some() {
+            Self::Error
+        } else if value.to_bool().is_some() {
+            Self::Bool
+        } else if value.to_i64().is_some() || value.to_u64().is_some() || value.to_f64().is_some() {
+            Self::Number
+        } else if value.to_cow_str().is_some() || value.to_char().is_some() {
+            Self::String
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Whether `target` is `module` or one of its child modules.
+fn is_target_or_child(target: &str, module: &str) -> bool {
+    target
+        .strip_prefix(module)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// 64-bit FNV-1a hash, used instead of `DefaultHasher` so target colours stay
+/// the same across runs and Rust versions.
+pub(crate) fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 #[cfg(test)]
@@ -382,6 +584,7 @@ mod tests {
         assert!(colors.caller_file.is_some());
         assert!(colors.caller_line.is_some());
         assert!(colors.target.is_some());
+        assert!(colors.thread.is_some());
         assert!(colors.attr_key.is_some());
         assert!(colors.attr_value.is_some());
     }
@@ -396,6 +599,119 @@ mod tests {
         assert!(colors.level_color(Level::Trace).is_some());
     }
 
+    #[test]
+    fn test_target_color_defaults_to_target() {
+        let colors = Colors::default();
+        assert_eq!(colors.target_color("app::db"), colors.target.as_ref());
+    }
+
+    #[test]
+    fn test_target_color_hashed_is_stable() {
+        let colors = Colors {
+            target_hashed: true,
+            ..Colors::default()
+        };
+        let first = colors.target_color("app::db").copied();
+        assert_eq!(colors.target_color("app::db").copied(), first);
+        assert!(colors.target_palette.contains(&first.unwrap()));
+
+        // Targets spread across the palette
+        let distinct: Vec<_> = ["app", "app::db", "app::http", "app::auth", "app::cache"]
+            .iter()
+            .map(|t| colors.target_color(t).copied())
+            .collect();
+        assert!(distinct.iter().any(|c| *c != first));
+
+        // FNV-1a is fixed, so the choice never changes between runs
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_target_color_overrides() {
+        let colors = Colors {
+            target_hashed: true,
+            target_overrides: BTreeMap::from([
+                ("app".to_string(), Color::red()),
+                ("app::db".to_string(), Color::blue()),
+            ]),
+            ..Colors::default()
+        };
+        assert_eq!(colors.target_color("app"), Some(&Color::red()));
+        assert_eq!(colors.target_color("app::http"), Some(&Color::red()));
+        // The most specific override wins
+        assert_eq!(colors.target_color("app::db::pool"), Some(&Color::blue()));
+        // Only whole segments match
+        assert_ne!(colors.target_color("apple"), Some(&Color::red()));
+    }
+
+    #[test]
+    fn test_target_color_empty_palette_falls_back() {
+        let colors = Colors {
+            target_hashed: true,
+            target_palette: Vec::new(),
+            ..Colors::default()
+        };
+        assert_eq!(colors.target_color("app"), colors.target.as_ref());
+    }
+
+    #[test]
+    fn test_value_kind_of() {
+        let err = std::io::Error::other("boom");
+        assert_eq!(ValueKind::of(&Value::from(42)), ValueKind::Number);
+        assert_eq!(ValueKind::of(&Value::from(-7i64)), ValueKind::Number);
+        assert_eq!(ValueKind::of(&Value::from(1.5)), ValueKind::Number);
+        assert_eq!(ValueKind::of(&Value::from(true)), ValueKind::Bool);
+        assert_eq!(ValueKind::of(&Value::from("42")), ValueKind::String);
+        assert_eq!(ValueKind::of(&Value::from('x')), ValueKind::String);
+        assert_eq!(
+            ValueKind::of(&Value::from_dyn_error(&err)),
+            ValueKind::Error
+        );
+        assert_eq!(ValueKind::of(&Value::from_debug(&[1, 2])), ValueKind::Other);
+    }
+
+    #[test]
+    fn test_attr_colors_by_kind() {
+        let colors = Colors {
+            attr_number: Some(Color::magenta()),
+            attr_error: Some(Color::red()),
+            ..Colors::default()
+        };
+        assert_eq!(
+            colors.attr_value_color("count", ValueKind::Number),
+            Some(&Color::magenta())
+        );
+        assert_eq!(
+            colors.attr_value_color("err", ValueKind::Error),
+            Some(&Color::red())
+        );
+        // Kinds without a colour fall back to attr_value
+        assert_eq!(
+            colors.attr_value_color("ok", ValueKind::Bool),
+            colors.attr_value.as_ref()
+        );
+        assert_eq!(colors.attr_key_color("count"), colors.attr_key.as_ref());
+    }
+
+    #[test]
+    fn test_attr_overrides() {
+        let colors = Colors {
+            attr_number: Some(Color::magenta()),
+            attr_overrides: BTreeMap::from([("duration_ms".to_string(), Color::blue())]),
+            ..Colors::default()
+        };
+        assert_eq!(colors.attr_key_color("duration_ms"), Some(&Color::blue()));
+        assert_eq!(
+            colors.attr_value_color("duration_ms", ValueKind::Number),
+            Some(&Color::blue())
+        );
+        assert_eq!(
+            colors.attr_value_color("other_ms", ValueKind::Number),
+            Some(&Color::magenta())
+        );
+    }
+
     #[test]
     fn test_color_attribute_default() {
         let attr = ColorAttribute::default();
@@ -600,6 +916,7 @@ mod tests {
             target: None,
             attr_key: None,
             attr_value: None,
+            ..Colors::default()
         };
 
         assert!(empty_colors.level_color(Level::Error).is_none());
@@ -627,6 +944,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn test_color_clone() {
         let c1 = Color::new(ColorAttribute::Red, ColorAttribute::Blue);
         let c2 = c1.clone();
@@ -634,6 +952,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn test_color_attribute_clone() {
         let attr = ColorAttribute::HiGreen;
         let cloned = attr.clone();
@@ -675,8 +994,10 @@ mod tests {
         let c2 = Colors::default();
         assert_eq!(c1, c2);
 
-        let mut c3 = Colors::default();
-        c3.message = None;
+        let c3 = Colors {
+            message: None,
+            ..Colors::default()
+        };
         assert_ne!(c1, c3);
     }
 
@@ -728,6 +1049,21 @@ mod tests {
         assert!(colors.attr_value.is_some());
     }
 
+    #[test]
+    fn test_colors_setters() {
+        let colors = Colors::default()
+            .with_message(None)
+            .with_thread(Some(Color::blue()))
+            .with_target_hashed(true)
+            .with_attr_overrides(BTreeMap::from([("error".to_string(), Color::red())]));
+
+        assert_eq!(colors.message, None);
+        assert_eq!(colors.thread, Some(Color::blue()));
+        assert!(colors.target_hashed);
+        assert_eq!(colors.attr_overrides["error"], Color::red());
+        assert_eq!(colors.arrow, Colors::default().arrow);
+    }
+
     #[test]
     fn test_color_attribute_serialize_deserialize() {
         let attr = ColorAttribute::HiCyan;
@@ -776,8 +1112,17 @@ mod tests {
             caller_file: None,
             caller_line: None,
             target: Some(Color::hi_yellow()),
+            target_hashed: true,
+            target_palette: vec![Color::red(), Color::blue()],
+            target_overrides: BTreeMap::from([("app::db".to_string(), Color::green())]),
+            thread: None,
             attr_key: None,
             attr_value: Some(Color::cyan()),
+            attr_number: Some(Color::magenta()),
+            attr_bool: None,
+            attr_string: None,
+            attr_error: Some(Color::red()),
+            attr_overrides: BTreeMap::from([("error".to_string(), Color::hi_red())]),
         };
         let serialized = serde_json::to_string(&partial_colors).unwrap();
         let deserialized: Colors = serde_json::from_str(&serialized).unwrap();
@@ -896,6 +1241,7 @@ mod tests {
             target: None,
             attr_key: None,
             attr_value: None,
+            ..Colors::default()
         };
 
         // Verify all level_color calls return None
@@ -1082,5 +1428,28 @@ mod tests {
         assert!(colors.caller_line.is_some());
         assert!(colors.attr_key.is_some());
         assert!(colors.attr_value.is_some());
+        assert!(!colors.target_hashed);
+        assert_eq!(colors.target_palette, default_target_palette());
+        assert!(colors.target_overrides.is_empty());
+        assert_eq!(colors.thread, Some(Color::magenta()));
+        assert!(colors.attr_number.is_none());
+        assert!(colors.attr_error.is_none());
+        assert!(colors.attr_overrides.is_empty());
+    }
+
+    #[test]
+    fn test_colors_target_overrides_from_toml() {
+        let toml = r#"
+            target_hashed = true
+            target_palette = [{ fg = "Red", bg = "Reset" }, { fg = "Blue", bg = "Reset" }]
+
+            [target_overrides]
+            "app::db" = { fg = "Green", bg = "Reset" }
+        "#;
+
+        let colors: Colors = toml::from_str(toml).expect("Failed to deserialize");
+        assert!(colors.target_hashed);
+        assert_eq!(colors.target_palette, vec![Color::red(), Color::blue()]);
+        assert_eq!(colors.target_color("app::db::pool"), Some(&Color::green()));
     }
 }
@@ -0,0 +1,296 @@
+//! Scoped contextual fields.
+//!
+//! This module lets a scope attach key-value pairs to every record logged
+//! inside it, e.g. a request ID, without repeating them at each call site.
+//! Fields are kept per thread with [`with_fields`] and [`push_field`], and
+//! with the `tokio` feature, per task with [`with_task_fields`] so they
+//! follow a future across worker threads.
+//!
+//! A record's own fields take precedence over scoped fields with the same
+//! key, and inner scopes take precedence over outer ones.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A scoped field: key and rendered value.
+type Field = (String, String);
+
+thread_local! {
+    static THREAD_FIELDS: RefCell<Vec<Field>> = const { RefCell::new(Vec::new()) };
+}
+
+#[cfg(feature = "tokio")]
+tokio::task_local! {
+    static TASK_FIELDS: Vec<Field>;
+}
+
+/// Runs `f` with `fields` attached to every record it logs on this thread.
+///
+/// # Examples
+///
+/// ```
+/// let request_id = 42;
+/// twyg::with_fields([("request_id", request_id)], || {
+///     // Logged as: "Handling request: request_id={42}"
+///     log::info!("Handling request");
+/// });
+/// ```
+pub fn with_fields<I, K, V, F, R>(fields: I, f: F) -> R
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: fmt::Display,
+    F: FnOnce() -> R,
+{
+    let _guard = push_all(fields);
+    f()
+}
+
+/// Attaches a field to every record logged on this thread until the
+/// returned guard is dropped.
+///
+/// Guards should be dropped in the reverse order they were created in, as
+/// happens naturally with scopes: dropping a guard also removes fields
+/// pushed after it.
+///
+/// # Examples
+///
+/// ```
+/// let _guard = twyg::push_field("request_id", 42);
+/// log::info!("Handling request");
+/// ```
+pub fn push_field(key: impl Into<String>, value: impl fmt::Display) -> FieldGuard {
+    push_all([(key, value)])
+}
+
+fn push_all<I, K, V>(fields: I) -> FieldGuard
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: fmt::Display,
+{
+    THREAD_FIELDS.with_borrow_mut(|stack| {
+        let len = stack.len();
+        stack.extend(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.to_string())),
+        );
+        FieldGuard {
+            len,
+            _not_send: PhantomData,
+        }
+    })
+}
+
+/// Removes the fields pushed by [`push_field`] when dropped.
+#[must_use = "the field is removed as soon as the guard is dropped"]
+pub struct FieldGuard {
+    /// Length of the thread's field stack before the push.
+    len: usize,
+    /// The fields live on the current thread, so the guard must stay there.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl fmt::Debug for FieldGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldGuard").finish_non_exhaustive()
+    }
+}
+
+impl Drop for FieldGuard {
+    fn drop(&mut self) {
+        let _ = THREAD_FIELDS.try_with(|stack| stack.borrow_mut().truncate(self.len));
+    }
+}
+
+/// Runs `future` with `fields` attached to every record it logs, on
+/// whichever tokio worker thread polls it.
+///
+/// Fields from an enclosing `with_task_fields` are kept.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// twyg::with_task_fields([("request_id", 42)], async {
+///     log::info!("Handling request");
+/// })
+/// .await;
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub async fn with_task_fields<I, K, V, F>(fields: I, future: F) -> F::Output
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: fmt::Display,
+    F: std::future::Future,
+{
+    let mut scoped = TASK_FIELDS.try_with(Clone::clone).unwrap_or_default();
+    scoped.extend(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.to_string())),
+    );
+    TASK_FIELDS.scope(scoped, future).await
+}
+
+/// Calls `f` with the scoped fields in effect, innermost first. A key can
+/// appear more than once; only its first appearance is in effect.
+///
+/// Thread fields come before task fields, so a thread scope inside a task
+/// overrides the task's fields.
+pub(crate) fn with_scoped_fields<R>(
+    f: impl for<'a> FnOnce(&mut (dyn Iterator<Item = (&'a str, &'a str)> + 'a)) -> R,
+) -> R {
+    with_task(|task| {
+        let mut f = Some(f);
+        let result = THREAD_FIELDS.try_with(|thread| {
+            // Logging from a Display impl inside push_field would find the
+            // stack borrowed; its fields aren't attached yet anyway
+            let thread = thread.try_borrow().ok()?;
+            let f = f.take()?;
+            let result = f(&mut innermost_first(&thread, task));
+            Some(result)
+        });
+        match (result, f) {
+            (Ok(Some(result)), _) => result,
+            (_, Some(f)) => f(&mut innermost_first(&[], task)),
+            _ => unreachable!("scoped fields are visited once"),
+        }
+    })
+}
+
+fn innermost_first<'a>(
+    thread: &'a [Field],
+    task: &'a [Field],
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    thread
+        .iter()
+        .rev()
+        .chain(task.iter().rev())
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+}
+
+#[cfg(feature = "tokio")]
+fn with_task<R>(f: impl FnOnce(&[Field]) -> R) -> R {
+    let mut f = Some(f);
+    let result = TASK_FIELDS.try_with(|fields| f.take().map(|f| f(fields)));
+    match (result, f) {
+        (Ok(Some(result)), _) => result,
+        (_, Some(f)) => f(&[]),
+        _ => unreachable!("task fields are visited once"),
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+fn with_task<R>(f: impl FnOnce(&[Field]) -> R) -> R {
+    f(&[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The fields in effect, outermost first.
+    fn fields() -> Vec<(String, String)> {
+        let mut fields: Vec<(String, String)> = Vec::new();
+        with_scoped_fields(|scoped| {
+            for (key, value) in scoped {
+                if !fields.iter().any(|(k, _)| k == key) {
+                    fields.push((key.to_string(), value.to_string()));
+                }
+            }
+        });
+        fields.reverse();
+        fields
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_with_fields() {
+        assert!(fields().is_empty());
+        let result = with_fields([("request_id", 42)], || {
+            assert_eq!(fields(), pairs(&[("request_id", "42")]));
+            "done"
+        });
+        assert_eq!(result, "done");
+        assert!(fields().is_empty());
+    }
+
+    #[test]
+    fn test_with_fields_nested_overrides_outer() {
+        with_fields([("user", "alice"), ("step", "outer")], || {
+            with_fields([("step", "inner")], || {
+                assert_eq!(fields(), pairs(&[("user", "alice"), ("step", "inner")]));
+            });
+            assert_eq!(fields(), pairs(&[("user", "alice"), ("step", "outer")]));
+        });
+    }
+
+    #[test]
+    fn test_push_field_guard() {
+        {
+            let _request = push_field("request_id", "abc");
+            let _user = push_field("user", "alice");
+            assert_eq!(fields(), pairs(&[("request_id", "abc"), ("user", "alice")]));
+            assert!(fields().iter().any(|(k, _)| k == "user"));
+        }
+        assert!(fields().is_empty());
+    }
+
+    #[test]
+    fn test_with_fields_removed_on_panic() {
+        let result = std::panic::catch_unwind(|| {
+            with_fields([("request_id", 1)], || panic!("handler failed"));
+        });
+        assert!(result.is_err());
+        assert!(fields().is_empty());
+    }
+
+    #[test]
+    fn test_fields_are_per_thread() {
+        let _guard = push_field("request_id", 7);
+        let other = std::thread::spawn(fields).join().unwrap();
+        assert!(other.is_empty());
+        assert_eq!(fields(), pairs(&[("request_id", "7")]));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_with_task_fields() {
+        let seen = with_task_fields([("request_id", 42)], async {
+            let outer = fields();
+            let inner = with_task_fields([("user", "alice")], async {
+                tokio::task::yield_now().await;
+                fields()
+            })
+            .await;
+            (outer, inner)
+        })
+        .await;
+        assert_eq!(seen.0, pairs(&[("request_id", "42")]));
+        assert_eq!(seen.1, pairs(&[("request_id", "42"), ("user", "alice")]));
+        assert!(fields().is_empty());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_thread_fields_override_task_fields() {
+        with_task_fields([("step", "task")], async {
+            with_fields([("step", "thread")], || {
+                assert_eq!(fields(), pairs(&[("step", "thread")]));
+            });
+        })
+        .await;
+    }
+}
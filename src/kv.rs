@@ -0,0 +1,208 @@
+//! Structured field rendering.
+//!
+//! This module provides [`FieldLayout`], which controls whether a record's
+//! key-value pairs are written inline after the message or beneath it, one
+//! per line, along with [`ValueBrackets`] and [`FieldOrder`] for the syntax
+//! and order of the pairs.
+
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+use super::limit::write_marker;
+
+/// Where a record's key-value pairs are written.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{FieldLayout, OptsBuilder};
+///
+/// let opts = OptsBuilder::new()
+///     .field_layout(FieldLayout::Pretty)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldLayout {
+    /// After the message on the same line: "msg: user={alice}, id={7}"
+    #[default]
+    Inline,
+
+    /// Beneath the message, one per line with aligned keys; with the
+    /// `kv_serde` feature, nested serde values are expanded as indented
+    /// trees. Meant for local development
+    Pretty,
+}
+
+/// What surrounds each inline value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValueBrackets {
+    /// Braces: user={alice}
+    #[default]
+    Braces,
+
+    /// Double quotes, with quotes and backslashes in the value escaped:
+    /// user="alice"
+    Quotes,
+
+    /// Nothing: user=alice
+    None,
+}
+
+/// The order key-value pairs are written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldOrder {
+    /// The record's own pairs as logged, then scoped, static and process
+    /// fields
+    #[default]
+    Insertion,
+
+    /// All pairs sorted by key
+    Alphabetical,
+}
+
+/// A value cut to at most `max` terminal columns, followed by a marker
+/// giving the number of bytes dropped when anything was left out.
+pub(crate) struct Shortened<T> {
+    text: T,
+    max: Option<usize>,
+}
+
+impl<T: fmt::Display> Shortened<T> {
+    pub(crate) fn new(text: T, max: Option<usize>) -> Self {
+        Shortened { text, max }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Shortened<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(max) = self.max else {
+            return write!(f, "{}", self.text);
+        };
+        let mut writer = ShortWriter {
+            inner: f,
+            left: max,
+            dropped: 0,
+        };
+        write!(writer, "{}", self.text)?;
+        if writer.dropped > 0 {
+            write_marker(writer.inner, writer.dropped)?;
+        }
+        Ok(())
+    }
+}
+
+struct ShortWriter<'a, W> {
+    inner: &'a mut W,
+    /// Columns that may still be written.
+    left: usize,
+    dropped: usize,
+}
+
+impl<W: fmt::Write> fmt::Write for ShortWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.dropped > 0 {
+            self.dropped += s.len();
+            return Ok(());
+        }
+        for (i, c) in s.char_indices() {
+            let width = c.width().unwrap_or(0);
+            if width > self.left {
+                self.dropped = s.len() - i;
+                return self.inner.write_str(&s[..i]);
+            }
+            self.left -= width;
+        }
+        self.inner.write_str(s)
+    }
+}
+
+/// A value with double quotes and backslashes escaped, for quoting.
+pub(crate) struct Quoted<T>(pub(crate) T);
+
+impl<T: fmt::Display> fmt::Display for Quoted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(QuoteWriter(f), "{}", self.0)
+    }
+}
+
+struct QuoteWriter<'a, W>(&'a mut W);
+
+impl<W: fmt::Write> fmt::Write for QuoteWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut plain = 0;
+        for (i, c) in s.char_indices() {
+            if c == '"' || c == '\\' {
+                self.0.write_str(&s[plain..i])?;
+                self.0.write_char('\\')?;
+                plain = i;
+            }
+        }
+        self.0.write_str(&s[plain..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortened() {
+        assert_eq!(Shortened::new("abcdef", None).to_string(), "abcdef");
+        assert_eq!(Shortened::new("abcdef", Some(6)).to_string(), "abcdef");
+        assert_eq!(
+            Shortened::new("abcdef", Some(3)).to_string(),
+            "abc…[+3 bytes]"
+        );
+        assert_eq!(Shortened::new("äöüß", Some(2)).to_string(), "äö…[+4 bytes]");
+        assert_eq!(Shortened::new("abc", Some(0)).to_string(), "…[+3 bytes]");
+        assert_eq!(Shortened::new("", Some(0)).to_string(), "");
+        assert_eq!(
+            Shortened::new("日本語", Some(4)).to_string(),
+            "日本…[+3 bytes]"
+        );
+        assert_eq!(
+            Shortened::new("日本語", Some(5)).to_string(),
+            "日本…[+3 bytes]"
+        );
+    }
+
+    #[test]
+    fn test_shortened_across_writes() {
+        let value = format_args!("{}{}", "abc", "def");
+        assert_eq!(
+            Shortened::new(value, Some(4)).to_string(),
+            "abcd…[+2 bytes]"
+        );
+        let value = format_args!("{}{}", "abc", "def");
+        assert_eq!(Shortened::new(value, Some(3)).to_string(), "abc…[+3 bytes]");
+    }
+
+    #[test]
+    fn test_quoted() {
+        assert_eq!(Quoted("alice").to_string(), "alice");
+        assert_eq!(
+            Quoted(r#"say "hi" \ bye"#).to_string(),
+            r#"say \"hi\" \\ bye"#
+        );
+    }
+
+    #[test]
+    fn test_value_brackets_and_order_defaults() {
+        assert_eq!(ValueBrackets::default(), ValueBrackets::Braces);
+        assert_eq!(FieldOrder::default(), FieldOrder::Insertion);
+    }
+
+    #[test]
+    fn test_field_layout_default() {
+        assert_eq!(FieldLayout::default(), FieldLayout::Inline);
+    }
+
+    #[test]
+    fn test_field_layout_serde() {
+        let layout: FieldLayout = serde_json::from_str(r#""Pretty""#).unwrap();
+        assert_eq!(layout, FieldLayout::Pretty);
+    }
+}
@@ -1,12 +1,15 @@
 //! Log level types and conversions.
 //!
-//! This module provides the [`LogLevel`] enum for type-safe log level configuration.
+//! This module provides the [`LogLevel`] enum for type-safe log level configuration,
+//! and [`LevelLabels`] for how levels are displayed.
 
-use log::LevelFilter;
+use log::{Level, LevelFilter};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
 
+use unicode_width::UnicodeWidthStr;
+
 /// Log level for filtering messages.
 ///
 /// Represents the minimum severity level for log messages to be displayed.
@@ -127,6 +130,113 @@ impl From<LogLevel> for LevelFilter {
     }
 }
 
+/// The label shown for each level.
+///
+/// Padded levels take the width of the widest label, or `pad_amount` columns
+/// if that is set and wider.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{CustomLevelLabels, LevelLabels, OptsBuilder};
+///
+/// let opts = OptsBuilder::new()
+///     .level_labels(LevelLabels::Short)
+///     .build()
+///     .unwrap();
+///
+/// let opts = OptsBuilder::new()
+///     .level_labels(LevelLabels::Custom(CustomLevelLabels {
+///         error: "FAIL".to_string(),
+///         ..CustomLevelLabels::default()
+///     }))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelLabels {
+    /// Upper case names: "ERROR", "WARN", "INFO", "DEBUG", "TRACE"
+    #[default]
+    Standard,
+
+    /// Three-letter abbreviations: "ERR", "WRN", "INF", "DBG", "TRC"
+    Short,
+
+    /// Lower case names: "error", "warn", "info", "debug", "trace"
+    Lowercase,
+
+    /// Single letters: "E", "W", "I", "D", "T"
+    Letter,
+
+    /// Emoji icons: "🔥", "⚠️", "ℹ️", "🐛", "🔍"
+    Icons,
+
+    /// Labels of your own; levels left out keep their standard name
+    Custom(CustomLevelLabels),
+}
+
+/// Labels for [`LevelLabels::Custom`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomLevelLabels {
+    pub error: String,
+    pub warn: String,
+    pub info: String,
+    pub debug: String,
+    pub trace: String,
+}
+
+impl Default for CustomLevelLabels {
+    fn default() -> Self {
+        Self {
+            error: "ERROR".to_string(),
+            warn: "WARN".to_string(),
+            info: "INFO".to_string(),
+            debug: "DEBUG".to_string(),
+            trace: "TRACE".to_string(),
+        }
+    }
+}
+
+impl LevelLabels {
+    /// Returns the label shown for `level`.
+    pub fn label(&self, level: Level) -> &str {
+        let labels: [&str; 5] = match self {
+            Self::Standard => return level.as_str(),
+            Self::Short => ["ERR", "WRN", "INF", "DBG", "TRC"],
+            Self::Lowercase => ["error", "warn", "info", "debug", "trace"],
+            Self::Letter => ["E", "W", "I", "D", "T"],
+            Self::Icons => ["🔥", "⚠️", "ℹ️", "🐛", "🔍"],
+            Self::Custom(custom) => {
+                return match level {
+                    Level::Error => &custom.error,
+                    Level::Warn => &custom.warn,
+                    Level::Info => &custom.info,
+                    Level::Debug => &custom.debug,
+                    Level::Trace => &custom.trace,
+                }
+            }
+        };
+        // Level discriminants run from Error = 1 to Trace = 5
+        labels[level as usize - 1]
+    }
+
+    /// Returns the display width of the widest label.
+    pub fn width(&self) -> usize {
+        [
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+        ]
+        .into_iter()
+        .map(|level| self.label(level).width())
+        .max()
+        .unwrap_or(0)
+    }
+}
+
 // Backwards compatibility helpers (deprecated)
 #[deprecated(since = "0.6.0", note = "Use LogLevel::Trace instead")]
 pub fn trace() -> Option<String> {
@@ -162,6 +272,52 @@ pub fn fatal() -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_level_labels_presets() {
+        assert_eq!(LevelLabels::Standard.label(Level::Warn), "WARN");
+        assert_eq!(LevelLabels::Short.label(Level::Error), "ERR");
+        assert_eq!(LevelLabels::Short.label(Level::Trace), "TRC");
+        assert_eq!(LevelLabels::Lowercase.label(Level::Info), "info");
+        assert_eq!(LevelLabels::Letter.label(Level::Debug), "D");
+        assert_eq!(LevelLabels::Icons.label(Level::Error), "🔥");
+        assert_eq!(LevelLabels::Icons.label(Level::Warn), "⚠️");
+    }
+
+    #[test]
+    fn test_level_labels_width() {
+        assert_eq!(LevelLabels::Standard.width(), 5);
+        assert_eq!(LevelLabels::Short.width(), 3);
+        assert_eq!(LevelLabels::Letter.width(), 1);
+        assert_eq!(LevelLabels::Icons.width(), 2);
+    }
+
+    #[test]
+    fn test_level_labels_custom() {
+        let labels = LevelLabels::Custom(CustomLevelLabels {
+            error: "FAILURE".to_string(),
+            ..CustomLevelLabels::default()
+        });
+        assert_eq!(labels.label(Level::Error), "FAILURE");
+        assert_eq!(labels.label(Level::Info), "INFO");
+        assert_eq!(labels.width(), 7);
+    }
+
+    #[test]
+    fn test_level_labels_serde() {
+        #[derive(Deserialize)]
+        struct Config {
+            labels: LevelLabels,
+        }
+
+        let config: Config = serde_json::from_str(r#"{"labels": "Short"}"#).unwrap();
+        assert_eq!(config.labels, LevelLabels::Short);
+
+        let config: Config =
+            serde_json::from_str(r#"{"labels": {"Custom": {"warn": "WARNING"}}}"#).unwrap();
+        assert_eq!(config.labels.label(Level::Warn), "WARNING");
+        assert_eq!(config.labels.label(Level::Debug), "DEBUG");
+    }
+
     #[test]
     fn test_log_level_as_str() {
         assert_eq!(LogLevel::Trace.as_str(), "trace");
@@ -1,31 +1,95 @@
+mod caller;
+mod clock;
 pub mod color;
+mod context;
 pub mod error;
+mod kv;
 pub mod level;
+mod limit;
 pub mod logger;
+mod macros;
 pub mod opts;
 pub mod out;
 pub mod output;
+mod process;
+mod ratelimit;
+mod redact;
+mod sanitize;
+mod target;
+mod thread;
 pub mod timestamp;
 
+pub use caller::CallerPath;
+pub use clock::{Clock, ManualClock, SystemClock};
 pub use color::{Color, ColorAttribute, Colors};
+#[cfg(feature = "tokio")]
+pub use context::with_task_fields;
+pub use context::{push_field, with_fields, FieldGuard};
 pub use error::{Result, TwygError};
-pub use level::LogLevel;
+pub use kv::{FieldLayout, FieldOrder, ValueBrackets};
+pub use level::{CustomLevelLabels, LevelLabels, LogLevel};
 pub use logger::Logger;
-pub use opts::{Opts, OptsBuilder, PadSide};
+pub use opts::{Opts, OptsBuilder, PadSide, TruncateSide};
 pub use out::{STDERR, STDOUT};
 pub use output::Output;
-pub use timestamp::TSFormat;
+pub use process::{Hostname, ProcessInfo};
+pub use ratelimit::{RateLimit, RateLimitKey};
+pub use redact::{MaskStyle, Redaction};
+pub use sanitize::NewlineMode;
+pub use target::TargetFormat;
+pub use thread::ThreadFormat;
+pub use timestamp::{TSFormat, Timezone};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use log;
+}
 
 /// Sets up the twyg logger based upon the provided options.
 ///
 /// The options (see the `twyg::Opts` struct) support the following configuration:
 ///
 /// * `coloured`: setting to false will disable ANSI colors in the logging output
+/// * `file_coloured`: setting to true keeps ANSI colors when writing to a file
+///   (file output is plain by default)
 /// * `output`: specify stdout, stderr, or a file path for log output
 /// * `level`: log level (Trace, Debug, Info, Warn, Error)
 /// * `report_caller`: setting to true will output the filename and line number
 ///   where the logging call was made
+/// * `caller_path`: how the caller's file is shown (full, basename, last N
+///   components, with a prefix stripped, or the module path instead)
+/// * `report_column`: setting to true will also output the column, for calls
+///   made through twyg's own logging macros (e.g. `twyg::info!`)
 /// * `time_format`: custom time format string (chrono format)
+/// * `timezone`: render timestamps in local time, UTC, a fixed offset, or an
+///   IANA zone (with the `tz` feature)
+/// * `clock`: the time source, e.g. a `ManualClock` for deterministic output
+/// * `level_labels`: the label shown for each level, e.g. "WRN" or an icon;
+///   padded levels are widened to fit the widest label
+/// * `target_format`, `pad_target`: shorten the target and render it at a
+///   fixed width
+/// * `report_thread`, `thread_format`, `pad_thread`: show the logging thread's
+///   name or ID, optionally at a fixed width
+/// * `report_process`, `process`: show the service name, version, environment,
+///   hostname and pid as fields on every line
+/// * `fields`: static key-value pairs added to every record; see also
+///   [`with_fields`] and [`push_field`] for fields scoped to a block of code
+/// * `redaction`: mask the values of sensitive fields such as `password`, and
+///   with the `regex` feature, matching text in messages
+/// * `sanitize`, `newline_mode`: escape control characters and strip ANSI
+///   escape sequences in messages, fields, targets and thread names, escaping
+///   newlines or indenting continuation lines
+/// * `indent_multiline`, `gutter_char`: line up the continuation lines of
+///   multi-line messages under the message, optionally with a gutter
+/// * `field_layout`: write key-value pairs inline after the message, or
+///   beneath it one per line
+/// * `value_brackets`, `kv_separator`, `pair_separator`, `field_order`,
+///   `max_value_len`: the syntax and order of key-value pairs, and a limit
+///   on value length
+/// * `max_message_len`, `max_line_len`: cut oversized messages and lines
+///   short, with a marker giving the number of bytes dropped
+/// * `rate_limit`: cap the records written per call site or target in each
+///   interval, summarising the ones suppressed
 ///
 /// With the options set, call the setup function, passing the opts as the argument.
 ///
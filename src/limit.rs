@@ -0,0 +1,283 @@
+//! Message and line length limits.
+//!
+//! This module cuts oversized messages and lines short at a character
+//! boundary, replacing the rest with a marker that says how many bytes were
+//! dropped: "…[+52133 bytes]". The dropped text is counted but never stored.
+
+use std::fmt::{self, Write};
+
+/// Resets colours when a coloured line is cut short.
+const RESET: &str = "\u{1b}[0m";
+
+/// Writes the marker shown in place of `dropped` bytes.
+pub(crate) fn write_marker(out: &mut impl fmt::Write, dropped: usize) -> fmt::Result {
+    write!(out, "…[+{} bytes]", dropped)
+}
+
+/// Returns the length of the marker for `dropped` bytes.
+fn marker_len(dropped: usize) -> usize {
+    let mut len = Count(0);
+    let _ = write_marker(&mut len, dropped);
+    len.0
+}
+
+/// A byte counting writer.
+struct Count(usize);
+
+impl fmt::Write for Count {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Text cut to at most `max` bytes, followed by a marker when anything was
+/// dropped.
+pub(crate) struct Truncated<T> {
+    text: T,
+    max: Option<usize>,
+}
+
+impl<T: fmt::Display> Truncated<T> {
+    pub(crate) fn new(text: T, max: Option<usize>) -> Self {
+        Truncated { text, max }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Truncated<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(max) = self.max else {
+            return write!(f, "{}", self.text);
+        };
+        let mut writer = TruncatingWriter {
+            inner: f,
+            left: max,
+            dropped: 0,
+        };
+        write!(writer, "{}", self.text)?;
+        if writer.dropped > 0 {
+            write_marker(writer.inner, writer.dropped)?;
+        }
+        Ok(())
+    }
+}
+
+struct TruncatingWriter<'a, W> {
+    inner: &'a mut W,
+    /// Bytes that may still be written.
+    left: usize,
+    dropped: usize,
+}
+
+impl<W: fmt::Write> fmt::Write for TruncatingWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.dropped > 0 || s.len() > self.left {
+            let end = floor_char_boundary(s, if self.dropped > 0 { 0 } else { self.left });
+            self.inner.write_str(&s[..end])?;
+            self.left = 0;
+            self.dropped += s.len() - end;
+            return Ok(());
+        }
+        self.left -= s.len();
+        self.inner.write_str(s)
+    }
+}
+
+/// Cuts each `\n`-separated line of `text` to at most `max` bytes with
+/// [`limit_line`], so a multi-line record keeps every line.
+pub(crate) fn limit_lines(text: &mut String, max: usize) {
+    if text.split('\n').all(|line| line.len() <= max) {
+        return;
+    }
+
+    let mut limited = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            limited.push('\n');
+        }
+        if line.len() <= max {
+            limited.push_str(line);
+        } else {
+            let mut line = line.to_string();
+            limit_line(&mut line, max);
+            limited.push_str(&line);
+        }
+    }
+    *text = limited;
+}
+
+/// Cuts `line` to at most `max` visible bytes, ending it with a marker,
+/// and a colour reset if the kept part is coloured. Escape sequences don't
+/// count towards the limit and aren't split, so coloured and plain lines
+/// are cut at the same place; if `max` can't fit the marker, the line is
+/// cut without one.
+fn limit_line(line: &mut String, max: usize) {
+    let (_, visible) = visible_prefix(line, usize::MAX);
+    if visible <= max {
+        return;
+    }
+
+    // Room for the longest marker this line could need
+    let room = max.checked_sub(marker_len(visible));
+    let (end, kept) = visible_prefix(line, room.unwrap_or(max));
+    let coloured = line[..end].contains('\u{1b}');
+    line.truncate(end);
+    if coloured {
+        line.push_str(RESET);
+    }
+    if room.is_some() {
+        let _ = write_marker(line, visible - kept);
+    }
+}
+
+/// Returns where the longest prefix of `s` with at most `max` visible bytes
+/// ends, and how many visible bytes it has. Bytes of ANSI escape sequences
+/// aren't visible; the prefix ends after its last visible character.
+fn visible_prefix(s: &str, max: usize) -> (usize, usize) {
+    let mut chars = s.char_indices().peekable();
+    let (mut end, mut visible) = (0, 0);
+    while let Some((i, c)) = chars.next() {
+        if c == '\u{1b}' {
+            // `ESC [ parameters final`, or a two-character sequence
+            if chars.next_if(|&(_, c)| c == '[').is_some() {
+                while chars
+                    .next_if(|&(_, c)| !('\u{40}'..='\u{7e}').contains(&c))
+                    .is_some()
+                {}
+            }
+            chars.next();
+            continue;
+        }
+        if visible + c.len_utf8() > max {
+            break;
+        }
+        visible += c.len_utf8();
+        end = i + c.len_utf8();
+    }
+    (end, visible)
+}
+
+/// Returns the largest char boundary in `s` at or before `index`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    (0..=index)
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncated_unlimited() {
+        assert_eq!(Truncated::new("hello", None).to_string(), "hello");
+    }
+
+    #[test]
+    fn test_truncated_within_limit() {
+        assert_eq!(Truncated::new("hello", Some(5)).to_string(), "hello");
+    }
+
+    #[test]
+    fn test_truncated_over_limit() {
+        assert_eq!(
+            Truncated::new("hello world", Some(5)).to_string(),
+            "hello…[+6 bytes]"
+        );
+    }
+
+    #[test]
+    fn test_truncated_at_char_boundary() {
+        // "é" is two bytes; cutting inside it keeps neither half
+        assert_eq!(
+            Truncated::new("café au lait", Some(4)).to_string(),
+            "caf…[+10 bytes]"
+        );
+    }
+
+    #[test]
+    fn test_truncated_across_writes() {
+        let huge = vec![0u8; 1000];
+        let text = Truncated::new(format_args!("{:?}", huge), Some(6)).to_string();
+        assert_eq!(text, "[0, 0,…[+2994 bytes]");
+    }
+
+    #[test]
+    fn test_limit_line_within_limit() {
+        let mut line = "INFO [app] ▶ ok".to_string();
+        limit_line(&mut line, 100);
+        assert_eq!(line, "INFO [app] ▶ ok");
+    }
+
+    #[test]
+    fn test_limit_line_over_limit() {
+        let mut line = format!("INFO [app] ▶ {}", "x".repeat(100));
+        limit_line(&mut line, 40);
+        assert!(line.len() <= 40, "{line}");
+        assert_eq!(line, "INFO [app] ▶ xxxxxxxxxx…[+90 bytes]");
+    }
+
+    #[test]
+    fn test_limit_line_measures_visible_text() {
+        let mut line = format!("\u{1b}[32mINFO\u{1b}[0m \u{1b}[31m{}", "x".repeat(100));
+        limit_line(&mut line, 32);
+        assert_eq!(
+            line,
+            "\u{1b}[32mINFO\u{1b}[0m \u{1b}[31mxxxxxxxxxxxx\u{1b}[0m…[+88 bytes]"
+        );
+
+        // The same line uncoloured is cut at the same place
+        let mut plain = format!("INFO {}", "x".repeat(100));
+        limit_line(&mut plain, 32);
+        assert_eq!(plain, "INFO xxxxxxxxxxxx…[+88 bytes]");
+
+        // Escapes alone never push a line over the limit
+        let mut line = "\u{1b}[1;32mINFO\u{1b}[0m".to_string();
+        limit_line(&mut line, 4);
+        assert_eq!(line, "\u{1b}[1;32mINFO\u{1b}[0m");
+    }
+
+    #[test]
+    fn test_limit_line_too_small_for_marker() {
+        let mut line = "INFO [app] ▶ message".to_string();
+        limit_line(&mut line, 4);
+        assert_eq!(line, "INFO");
+
+        let mut line = "\u{1b}[32mINFO [app]\u{1b}[0m ▶ message".to_string();
+        limit_line(&mut line, 4);
+        assert_eq!(line, "\u{1b}[32mINFO\u{1b}[0m");
+    }
+
+    #[test]
+    fn test_limit_lines_cuts_each_line() {
+        let mut text = format!(
+            "INFO [app] ▶ ok\n    body = {}\n    id   = 7",
+            "x".repeat(100)
+        );
+        limit_lines(&mut text, 32);
+        assert_eq!(
+            text,
+            "INFO [app] ▶ ok\n    body = xxxxxx…[+94 bytes]\n    id   = 7"
+        );
+    }
+
+    #[test]
+    fn test_visible_prefix() {
+        assert_eq!(visible_prefix("", 10), (0, 0));
+        assert_eq!(visible_prefix("café", 4), (3, 3));
+        assert_eq!(visible_prefix("\u{1b}[31mab\u{1b}[0m", 1), (6, 1));
+        assert_eq!(visible_prefix("\u{1b}[31mab\u{1b}[0m", usize::MAX), (7, 2));
+        assert_eq!(visible_prefix("a\u{1b}cb", usize::MAX), (4, 2));
+    }
+
+    #[test]
+    fn test_floor_char_boundary() {
+        assert_eq!(floor_char_boundary("aé", 2), 1);
+        assert_eq!(floor_char_boundary("aé", 3), 3);
+        assert_eq!(floor_char_boundary("aé", 10), 3);
+    }
+}
@@ -6,27 +6,46 @@
 //!
 //! # Features
 //!
-//! - Zero-copy formatting using `write!()` instead of String allocation
+//! - Records rendered into a reusable per-thread buffer and written in one call
 //! - Three-tiered error recovery (normal → stderr → panic)
 //! - Mutex poison recovery for robust thread safety
 //! - BufWriter for efficient file I/O
 //! - Structured logging support via log crate's kv feature
 
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fmt::{self, Write as _};
 use std::fs::File;
 use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use chrono::Local;
+use log::kv::{Key, Source, Value, VisitSource};
 use log::{Level, LevelFilter, Log, Metadata, Record};
 use owo_colors::Stream;
 use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use super::color::Colors;
+use super::caller::CallerPath;
+use super::clock::Clock;
+use super::color::{Color, Colors, Painted, ValueKind};
+use super::context;
 use super::error::Result;
-use super::level::LogLevel;
-use super::opts::{Opts, PadSide};
+use super::kv::{FieldLayout, FieldOrder, Quoted, Shortened, ValueBrackets};
+use super::level::{LevelLabels, LogLevel};
+use super::limit::{limit_lines, Truncated};
+use super::macros::COLUMN_KEY;
+use super::opts::{validate_len_limit, Opts, PadSide, TruncateSide};
 use super::output::Output;
-use super::timestamp::TSFormat;
+use super::process::ProcessFields;
+use super::ratelimit::{Grouped, RateLimiter, Suppressed};
+use super::redact::Redactor;
+use super::sanitize::{sanitize_label, visible_width, NewlineMode, Sanitized};
+use super::target::TargetFormat;
+use super::thread::ThreadFormat;
+use super::timestamp::{Delta, Elapsed, StrftimeFormat, TSFormat, Zone};
 
 /// Output writer enum supporting stdout, stderr, and file output.
 enum OutputWriter {
@@ -36,11 +55,11 @@ enum OutputWriter {
 }
 
 impl OutputWriter {
-    fn write_fmt(&mut self, args: std::fmt::Arguments) -> io::Result<()> {
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
         match self {
-            OutputWriter::Stdout(w) => w.write_fmt(args),
-            OutputWriter::Stderr(w) => w.write_fmt(args),
-            OutputWriter::File(w) => w.write_fmt(args),
+            OutputWriter::Stdout(w) => w.write_all(buf),
+            OutputWriter::Stderr(w) => w.write_all(buf),
+            OutputWriter::File(w) => w.write_all(buf),
         }
     }
 
@@ -55,13 +74,50 @@ impl OutputWriter {
 
 /// Internal logger configuration.
 struct LoggerConfig {
-    stream: Stream,
+    /// Stream used for colour support detection, or `None` when this sink
+    /// is written without ANSI escape codes.
+    stream: Option<Stream>,
     max_level: LevelFilter,
     timestamp_format: TSFormat,
+    /// The timestamp format's chrono items, parsed once.
+    strftime: StrftimeFormat,
+    zone: Zone,
+    clock: Arc<dyn Clock>,
     report_caller: bool,
+    report_column: bool,
+    caller_path: CallerPath,
     pad_level: bool,
     pad_amount: usize,
+    level_labels: LevelLabels,
     pad_side: PadSide,
+    target_format: TargetFormat,
+    pad_target: bool,
+    target_pad_amount: usize,
+    target_pad_side: PadSide,
+    target_truncate_side: TruncateSide,
+    report_thread: bool,
+    thread_format: ThreadFormat,
+    /// Thread width, or 0 when the thread isn't padded.
+    thread_pad_amount: usize,
+    thread_pad_side: PadSide,
+    /// Static fields added to every record.
+    fields: Vec<(String, String)>,
+    redactor: Redactor,
+    /// Newline handling when messages and values are sanitised, or `None`
+    /// when they're written as they are.
+    sanitize: Option<NewlineMode>,
+    indent_multiline: bool,
+    gutter_char: Option<String>,
+    field_layout: FieldLayout,
+    value_brackets: ValueBrackets,
+    kv_separator: String,
+    pair_separator: String,
+    field_order: FieldOrder,
+    max_value_len: Option<usize>,
+    max_message_len: Option<usize>,
+    max_line_len: Option<usize>,
+    /// Process metadata fields, empty unless `report_process` is set.
+    process: ProcessFields,
     msg_separator: String,
     arrow_char: String,
     colors: Colors,
@@ -77,36 +133,390 @@ struct LoggerConfig {
 struct TwygLogger {
     output: Arc<Mutex<OutputWriter>>,
     config: LoggerConfig,
+    /// Monotonic clock reading at setup, for elapsed timestamps.
+    started: Duration,
+    /// Monotonic clock reading at the previous record, for delta timestamps.
+    ///
+    /// Only locked while the output lock is held.
+    last_record: Mutex<Duration>,
+    /// Identifies this logger's entries in the timestamp cache.
+    id: usize,
+    /// Records written per call site or target, when rate limited.
+    rate_limiter: Option<RateLimiter>,
+}
+
+/// Indentation of continuation lines when sanitising in
+/// [`NewlineMode::Indent`] without `indent_multiline`.
+const CONTINUATION_INDENT: &str = "    ";
+
+thread_local! {
+    /// Scratch buffer each record is rendered into before a single write.
+    static LINE_BUFFER: Cell<String> = const { Cell::new(String::new()) };
+
+    /// The last timestamp prefix rendered on this thread, keyed by the
+    /// logger that rendered it and its cache key.
+    static TIMESTAMP_CACHE: Cell<(Option<(usize, i64)>, String)> =
+        const { Cell::new((None, String::new())) };
+}
+
+/// Source of [`TwygLogger`] ids.
+static NEXT_LOGGER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Indentation of key-value pairs in [`FieldLayout::Pretty`].
+const FIELD_INDENT: &str = "    ";
+
+/// Extra indentation of each level of a nested value's tree.
+#[cfg(feature = "kv_serde")]
+const TREE_INDENT: usize = 2;
+
+/// Line buffers that grew past this capacity, e.g. for one huge record, are
+/// dropped rather than kept for the thread's lifetime.
+const LINE_BUFFER_RETAIN: usize = 64 * 1024;
+
+/// Runs `f` with this thread's cleared line buffer.
+///
+/// A record logged while another is being rendered on the same thread gets
+/// a fresh buffer.
+fn with_line_buffer<R>(f: impl FnOnce(&mut String) -> R) -> R {
+    let mut buffer = LINE_BUFFER.try_with(Cell::take).unwrap_or_default();
+    buffer.clear();
+    let result = f(&mut buffer);
+    if buffer.capacity() <= LINE_BUFFER_RETAIN {
+        let _ = LINE_BUFFER.try_with(|cell| cell.set(buffer));
+    }
+    result
+}
+
+impl LoggerConfig {
+    /// Creates the internal configuration from Opts.
+    ///
+    /// Colour is decided per sink: terminals follow `coloured`, while files
+    /// stay plain unless `file_coloured` is also set. An invalid timezone
+    /// falls back to local time and invalid redaction patterns are dropped;
+    /// `Logger::dispatch` rejects both beforehand.
+    fn new(opts: &Opts) -> Self {
+        let colour_enabled = if opts.output().is_file() {
+            opts.coloured() && opts.file_coloured()
+        } else {
+            opts.coloured()
+        };
+
+        let zone = match opts.timestamp_format() {
+            TSFormat::RFC3339Utc => Zone::Utc,
+            _ => opts.timezone().resolve().unwrap_or(Zone::Local),
+        };
+
+        LoggerConfig {
+            stream: colour_enabled.then(|| Stream::from(opts.output())),
+            max_level: LevelFilter::from(opts.level()),
+            timestamp_format: opts.timestamp_format().clone(),
+            strftime: StrftimeFormat::new(opts.timestamp_format().to_format_string()),
+            zone,
+            clock: Arc::clone(opts.clock()),
+            report_caller: opts.report_caller(),
+            report_column: opts.report_column(),
+            caller_path: opts.caller_path().clone(),
+            pad_level: opts.pad_level(),
+            pad_amount: opts.level_width(),
+            level_labels: opts.level_labels().clone(),
+            pad_side: opts.pad_side(),
+            target_format: opts.target_format(),
+            pad_target: opts.pad_target(),
+            target_pad_amount: opts.target_pad_amount(),
+            target_pad_side: opts.target_pad_side(),
+            target_truncate_side: opts.target_truncate_side(),
+            report_thread: opts.report_thread(),
+            thread_format: opts.thread_format(),
+            thread_pad_amount: if opts.pad_thread() {
+                opts.thread_pad_amount()
+            } else {
+                0
+            },
+            thread_pad_side: opts.thread_pad_side(),
+            fields: opts
+                .fields()
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            redactor: opts.redaction().compile().unwrap_or_default(),
+            sanitize: opts.sanitize().then(|| opts.newline_mode()),
+            indent_multiline: opts.indent_multiline(),
+            gutter_char: opts.gutter_char().map(str::to_string),
+            field_layout: opts.field_layout(),
+            value_brackets: opts.value_brackets(),
+            kv_separator: opts.kv_separator().to_string(),
+            pair_separator: opts.pair_separator().to_string(),
+            field_order: opts.field_order(),
+            max_value_len: opts.max_value_len(),
+            max_message_len: opts.max_message_len(),
+            max_line_len: opts.max_line_len(),
+            process: if opts.report_process() {
+                opts.process().fields()
+            } else {
+                ProcessFields::default()
+            },
+            msg_separator: opts.msg_separator().to_string(),
+            arrow_char: opts.arrow_char().to_string(),
+            colors: opts.colors().clone(),
+        }
+    }
+
+    /// Formats a target according to the target format and width options.
+    fn format_target<'a>(&self, target: &'a str) -> Fit<'a> {
+        let target = self.label(self.target_format.display(target));
+        if self.pad_target {
+            fit_width(
+                target,
+                self.target_pad_amount,
+                self.target_pad_side,
+                self.target_truncate_side,
+            )
+        } else {
+            pad_str(target, 0, PadSide::Right)
+        }
+    }
+
+    /// Applies an optional colour to text, leaving it plain for uncoloured sinks.
+    fn paint<'a, T: fmt::Display>(&self, color: Option<&'a Color>, text: T) -> Painted<'a, T> {
+        Painted::new(text, color, self.stream)
+    }
+
+    /// Returns a label such as a target, field key or caller file, sanitised
+    /// if enabled. Newlines in labels are always escaped.
+    fn label<'a>(&self, text: impl Into<Cow<'a, str>>) -> Cow<'a, str> {
+        let text = text.into();
+        if self.sanitize.is_none() {
+            return text;
+        }
+        match sanitize_label(&text) {
+            Cow::Owned(clean) => Cow::Owned(clean),
+            Cow::Borrowed(_) => text,
+        }
+    }
+
+    /// Writes logged text, such as a message or field value, sanitising it
+    /// if enabled.
+    fn write_text(
+        &self,
+        out: &mut String,
+        color: Option<&Color>,
+        text: impl fmt::Display,
+    ) -> fmt::Result {
+        match self.sanitize {
+            Some(mode) => write!(out, "{}", self.paint(color, Sanitized::new(text, mode))),
+            None => write!(out, "{}", self.paint(color, text)),
+        }
+    }
+
+    /// Writes a record's message, redacted, sanitised and truncated.
+    fn write_message(
+        &self,
+        out: &mut String,
+        color: Option<&Color>,
+        record: &Record,
+    ) -> fmt::Result {
+        if self.redactor.redacts_messages() {
+            let message = match record.args().as_str() {
+                Some(message) => Cow::Borrowed(message),
+                None => Cow::Owned(record.args().to_string()),
+            };
+            let message = self.redactor.redact_message(&message);
+            self.write_text(out, color, Truncated::new(message, self.max_message_len))
+        } else {
+            self.write_text(
+                out,
+                color,
+                Truncated::new(record.args(), self.max_message_len),
+            )
+        }
+    }
+
+    /// Returns whether continuation lines are indented: when multi-line
+    /// indenting or sanitising in [`NewlineMode::Indent`] is enabled.
+    fn indents_continuations(&self) -> bool {
+        self.indent_multiline || self.sanitize == Some(NewlineMode::Indent)
+    }
+
+    /// Indents the continuation lines of the message and fields written
+    /// from `message_start` on, if enabled.
+    fn indent_continuations(
+        &self,
+        out: &mut String,
+        arrow_start: usize,
+        message_start: usize,
+    ) -> fmt::Result {
+        if !self.indents_continuations() || !out[message_start..].contains('\n') {
+            return Ok(());
+        }
+
+        let continuation = if self.indent_multiline {
+            // Line up with the message, with any gutter under the arrow
+            let mut continuation = String::new();
+            match &self.gutter_char {
+                Some(gutter) => {
+                    let width = visible_width(&out[..arrow_start]);
+                    let pad = visible_width(&out[arrow_start..message_start])
+                        .saturating_sub(visible_width(gutter))
+                        .max(1);
+                    write!(
+                        continuation,
+                        "{:width$}{}{:pad$}",
+                        "",
+                        self.paint(self.colors.arrow.as_ref(), gutter),
+                        ""
+                    )?;
+                }
+                None => {
+                    let width = visible_width(&out[..message_start]);
+                    write!(continuation, "{:width$}", "")?;
+                }
+            }
+            Cow::Owned(continuation)
+        } else {
+            Cow::Borrowed(CONTINUATION_INDENT)
+        };
+
+        let text = out.split_off(message_start);
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+                out.push_str(&continuation);
+            }
+            out.push_str(line);
+        }
+        Ok(())
+    }
+
+    /// Writes a record's key-value pairs followed by the scoped and static
+    /// fields it doesn't override and any process metadata, led by the
+    /// message separator.
+    fn write_key_values(&self, out: &mut String, source: &dyn Source) {
+        context::with_scoped_fields(|scoped| {
+            let mut fields = self.collect_fields(source, scoped);
+            if self.field_order == FieldOrder::Alphabetical {
+                fields.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            }
+            let pretty = self.field_layout == FieldLayout::Pretty;
+            let mut writer = KeyValueWriter {
+                out,
+                config: self,
+                written: 0,
+                key_width: pretty.then(|| {
+                    fields
+                        .iter()
+                        .map(|(key, _)| self.label(key.as_str()).width())
+                        .max()
+                        .unwrap_or(0)
+                }),
+            };
+            for (key, value) in fields {
+                let _ = writer.visit_pair(key, value);
+            }
+        })
+    }
+
+    /// Writes a field value, cut short to `max_value_len` and, for inline
+    /// pairs, surrounded by its brackets.
+    fn write_value(
+        &self,
+        out: &mut String,
+        color: Option<&Color>,
+        value: impl fmt::Display,
+        inline: bool,
+    ) -> fmt::Result {
+        let value = Shortened::new(value, self.max_value_len);
+        if !inline {
+            return self.write_text(out, color, value);
+        }
+        match self.value_brackets {
+            ValueBrackets::Braces => self.write_text(out, color, format_args!("{{{}}}", value)),
+            ValueBrackets::Quotes => {
+                self.write_text(out, color, format_args!("\"{}\"", Quoted(value)))
+            }
+            ValueBrackets::None => self.write_text(out, color, value),
+        }
+    }
+
+    /// Returns the text of a field's value when it's masked whole: its key
+    /// is redacted, or it can't be walked for nested keys and its text names
+    /// a redacted one.
+    fn masked_text(&self, key: &str, value: &Value) -> Option<String> {
+        if self.redactor.redacts_key(key) {
+            return Some(value.to_string());
+        }
+        if ValueKind::of(value) != ValueKind::Other {
+            return None;
+        }
+        #[cfg(feature = "kv_serde")]
+        if nested(value).is_some() {
+            return None;
+        }
+        let text = value.to_string();
+        self.redactor.mentions_key(&text).then_some(text)
+    }
+
+    /// Returns a record's key-value pairs followed by the scoped, static and
+    /// process fields that no field before them sets, in that order.
+    ///
+    /// `scoped` yields the scoped fields innermost first, as
+    /// [`context::with_scoped_fields`] does.
+    fn collect_fields<'a, 's: 'a>(
+        &'a self,
+        source: &'a dyn Source,
+        scoped: &mut dyn Iterator<Item = (&'s str, &'s str)>,
+    ) -> Vec<(Key<'a>, Value<'a>)> {
+        let mut record = FieldCollector(Vec::new());
+        let _ = source.visit(&mut record);
+        let record = record.0;
+
+        let mut seen: HashSet<&str> = record.iter().map(|(key, _)| key.as_str()).collect();
+        let mut rest: Vec<(&str, Value)> = Vec::new();
+
+        // Gathered innermost first so inner scopes win, then written
+        // outermost first
+        for (key, value) in scoped {
+            if seen.insert(key) {
+                rest.push((key, Value::from(value)));
+            }
+        }
+        rest.reverse();
+
+        for (key, value) in &self.fields {
+            if seen.insert(key) {
+                rest.push((key, Value::from(value.as_str())));
+            }
+        }
+        for (key, value) in &self.process.strings {
+            if seen.insert(key) {
+                rest.push((key, Value::from(value.as_str())));
+            }
+        }
+        if let Some(pid) = self.process.pid.filter(|_| seen.insert("pid")) {
+            rest.push(("pid", Value::from(pid)));
+        }
+        drop(seen);
+
+        let mut fields = record;
+        fields.extend(
+            rest.into_iter()
+                .map(|(key, value)| (Key::from_str(key), value)),
+        );
+        fields
+    }
 }
 
 impl TwygLogger {
     /// Creates a new TwygLogger from Opts.
     fn new(opts: &Opts, output: OutputWriter) -> Self {
-        let stream = Stream::from(opts.output());
-        let max_level = LevelFilter::from(opts.level());
-        let timestamp_format = opts.timestamp_format().clone();
-        let report_caller = opts.report_caller();
-        let pad_level = opts.pad_level();
-        let pad_amount = opts.pad_amount();
-        let pad_side = opts.pad_side();
-        let msg_separator = opts.msg_separator().to_string();
-        let arrow_char = opts.arrow_char().to_string();
-        let colors = opts.colors().clone();
-
+        let config = LoggerConfig::new(opts);
+        let started = config.clock.monotonic();
         TwygLogger {
             output: Arc::new(Mutex::new(output)),
-            config: LoggerConfig {
-                stream,
-                max_level,
-                timestamp_format,
-                report_caller,
-                pad_level,
-                pad_amount,
-                pad_side,
-                msg_separator,
-                arrow_char,
-                colors,
-            },
+            config,
+            started,
+            last_record: Mutex::new(started),
+            id: NEXT_LOGGER_ID.fetch_add(1, Ordering::Relaxed),
+            rate_limiter: opts.rate_limit().and_then(|limit| limit.compile().ok()),
         }
     }
 
@@ -117,144 +527,215 @@ impl TwygLogger {
         self.output.lock().unwrap_or_else(|e| e.into_inner())
     }
 
-    /// Writes a log record to the output.
+    /// Writes the coloured timestamp and its trailing space, or nothing when
+    /// timestamps are omitted.
     ///
-    /// Uses zero-copy write!() formatting (fern pattern) instead of String allocation.
-    fn write_log(&self, record: &Record) -> io::Result<()> {
-        let mut writer = self.output_lock();
-        let ts_format = self.config.timestamp_format.to_format_string();
-        let timestamp = Local::now().format(ts_format);
-        let level = format_level(
-            record.level(),
-            &self.config.colors,
-            self.config.pad_level,
-            self.config.pad_amount,
-            self.config.pad_side,
-            self.config.stream,
-        );
-        let target = record.target();
-        let message = record.args();
-
-        // Extract key-value pairs for structured logging
-        let mut kv_collector = KeyValueCollector::new();
-        let _ = record.key_values().visit(&mut kv_collector);
-
-        // Use write!() for zero-copy formatting (fern pattern)
-        if self.config.report_caller {
-            // Format timestamp with config color
-            let timestamp_str = timestamp.to_string();
-            let timestamp_colored = self
-                .config
-                .colors
-                .timestamp
-                .as_ref()
-                .map(|c| c.apply(&timestamp_str, self.config.stream))
-                .unwrap_or(timestamp_str);
-
-            // Format caller file and line
-            let file = opt_str_or_placeholder(record.file());
-            let line = opt_u32_or_placeholder(record.line());
-            let caller_str = format!("{}:{}", file, line);
-            let caller_colored = self
-                .config
-                .colors
-                .caller_file
-                .as_ref()
-                .map(|c| c.apply(&caller_str, self.config.stream))
-                .unwrap_or(caller_str);
-
-            // Format target with config color
-            let target_colored = self
-                .config
-                .colors
-                .target
-                .as_ref()
-                .map(|c| c.apply(target, self.config.stream))
-                .unwrap_or_else(|| target.to_string());
-
-            // Format arrow with config color
-            let arrow_colored = self
-                .config
-                .colors
-                .arrow
-                .as_ref()
-                .map(|c| c.apply(&self.config.arrow_char, self.config.stream))
-                .unwrap_or_else(|| self.config.arrow_char.clone());
-
-            // Format message with config color
-            let message_str = message.to_string();
-            let message_colored = self
-                .config
-                .colors
-                .message
-                .as_ref()
-                .map(|c| c.apply(&message_str, self.config.stream))
-                .unwrap_or(message_str);
+    /// Relative timestamps must be written under the output lock, so they
+    /// follow the order records are written in. Wall-clock prefixes are
+    /// reused from a per-thread cache while the formatted text can't change,
+    /// e.g. within one second.
+    fn write_timestamp(&self, out: &mut String) -> fmt::Result {
+        let color = self.config.colors.timestamp.as_ref();
+        match &self.config.timestamp_format {
+            TSFormat::None => Ok(()),
+            TSFormat::Elapsed => {
+                let elapsed = self.config.clock.monotonic().saturating_sub(self.started);
+                write!(out, "{} ", self.config.paint(color, Elapsed(elapsed)))
+            }
+            TSFormat::Delta => {
+                let now = self.config.clock.monotonic();
+                let mut last = self.last_record.lock().unwrap_or_else(|e| e.into_inner());
+                let delta = now.saturating_sub(*last);
+                *last = now;
+                write!(out, "{} ", self.config.paint(color, Delta(delta)))
+            }
+            _ => {
+                let now = self.config.clock.now();
+                let timestamp = self.config.zone.display(now, &self.config.strftime);
+                let Some(key) = self.config.strftime.cache_key(now) else {
+                    return write!(out, "{} ", self.config.paint(color, timestamp));
+                };
+
+                let key = Some((self.id, key));
+                let (mut cached_key, mut rendered) =
+                    TIMESTAMP_CACHE.try_with(Cell::take).unwrap_or_default();
+                if cached_key != key {
+                    rendered.clear();
+                    write!(rendered, "{} ", self.config.paint(color, timestamp))?;
+                    cached_key = key;
+                }
+                out.push_str(&rendered);
+                let _ = TIMESTAMP_CACHE.try_with(|cell| cell.set((cached_key, rendered)));
+                Ok(())
+            }
+        }
+    }
 
-            write!(
-                writer,
-                "{} {} [{} {}] {} {}{}",
-                timestamp_colored,
-                level,
-                caller_colored,
-                target_colored,
-                arrow_colored,
-                message_colored,
-                kv_collector.format_pairs(&self.config)
-            )?;
-        } else {
-            // Format timestamp with config color
-            let timestamp_str = timestamp.to_string();
-            let timestamp_colored = self
-                .config
-                .colors
-                .timestamp
-                .as_ref()
-                .map(|c| c.apply(&timestamp_str, self.config.stream))
-                .unwrap_or(timestamp_str);
-
-            // Format target with config color
-            let target_colored = self
-                .config
-                .colors
-                .target
-                .as_ref()
-                .map(|c| c.apply(target, self.config.stream))
-                .unwrap_or_else(|| target.to_string());
-
-            // Format arrow with config color
-            let arrow_colored = self
-                .config
-                .colors
-                .arrow
-                .as_ref()
-                .map(|c| c.apply(&self.config.arrow_char, self.config.stream))
-                .unwrap_or_else(|| self.config.arrow_char.clone());
-
-            // Format message with config color
-            let message_str = message.to_string();
-            let message_colored = self
-                .config
-                .colors
-                .message
-                .as_ref()
-                .map(|c| c.apply(&message_str, self.config.stream))
-                .unwrap_or(message_str);
+    /// Ends a rendered line: applies the line length limit and appends the
+    /// newline.
+    fn finish_line(&self, out: &mut String) {
+        if let Some(max) = self.config.max_line_len {
+            limit_lines(out, max);
+        }
+        out.push('\n');
+    }
+
+    /// Renders a log record into `out`, from the level onwards.
+    fn format_record(&self, out: &mut String, record: &Record) -> fmt::Result {
+        let config = &self.config;
+        let colors = &config.colors;
+
+        write!(
+            out,
+            "{} ",
+            format_level(
+                record.level(),
+                &config.level_labels,
+                colors,
+                config.pad_level,
+                config.pad_amount,
+                config.pad_side,
+                config.stream,
+            )
+        )?;
+
+        if config.report_thread {
+            let thread = config
+                .thread_format
+                .current()
+                .sanitized(config.sanitize.is_some());
+            let color = colors.thread.as_ref();
+            let width = config.thread_pad_amount;
+            match config.thread_pad_side {
+                PadSide::Left => write!(
+                    out,
+                    "[{}] ",
+                    config.paint(color, format_args!("{:>width$}", thread))
+                )?,
+                PadSide::Right => write!(
+                    out,
+                    "[{}] ",
+                    config.paint(color, format_args!("{:<width$}", thread))
+                )?,
+            }
+        }
 
+        out.push('[');
+        if config.report_caller {
+            // Caller file, line and column, each with its own color
+            let file = config.label(opt_str_or_placeholder(
+                config
+                    .caller_path
+                    .display(record.file(), record.module_path()),
+            ));
             write!(
-                writer,
-                "{} {} [{}] {} {}{}",
-                timestamp_colored,
-                level,
-                target_colored,
-                arrow_colored,
-                message_colored,
-                kv_collector.format_pairs(&self.config)
+                out,
+                "{}:{}",
+                config.paint(colors.caller_file.as_ref(), file),
+                config.paint(
+                    colors.caller_line.as_ref(),
+                    opt_u32_or_placeholder(record.line())
+                )
             )?;
+            if let Some(column) = caller_column(record).filter(|_| config.report_column) {
+                write!(
+                    out,
+                    ":{}",
+                    config.paint(colors.caller_line.as_ref(), column)
+                )?;
+            }
+            out.push(' ');
+        }
+
+        write!(
+            out,
+            "{}] ",
+            config.paint(
+                colors.target_color(record.target()),
+                config.format_target(record.target())
+            ),
+        )?;
+        let arrow_start = out.len();
+        write!(
+            out,
+            "{} ",
+            config.paint(colors.arrow.as_ref(), &config.arrow_char)
+        )?;
+        let message_start = out.len();
+        config.write_message(out, colors.message.as_ref(), record)?;
+        // Pretty fields bring their own indentation
+        if config.field_layout == FieldLayout::Pretty {
+            config.indent_continuations(out, arrow_start, message_start)?;
+            config.write_key_values(out, record.key_values());
+        } else {
+            config.write_key_values(out, record.key_values());
+            config.indent_continuations(out, arrow_start, message_start)?;
         }
+        Ok(())
+    }
+
+    /// Writes a log record to the output.
+    ///
+    /// The record is rendered into this thread's line buffer before the
+    /// output lock is taken, then handed to the writer in a single call, so
+    /// a record logged while rendering another can't deadlock. Relative
+    /// timestamps are the exception: they're measured and prepended under
+    /// the lock, so they never run backwards in the output.
+    fn write_log(&self, record: &Record) -> io::Result<()> {
+        let relative = matches!(
+            self.config.timestamp_format,
+            TSFormat::Elapsed | TSFormat::Delta
+        );
+        with_line_buffer(|line| {
+            if !relative {
+                self.write_timestamp(line).map_err(io::Error::other)?;
+            }
+            self.format_record(line, record).map_err(io::Error::other)?;
+            if !relative {
+                self.finish_line(line);
+            }
+
+            let mut writer = self.output_lock();
+            if relative {
+                let mut timestamp = String::new();
+                self.write_timestamp(&mut timestamp)
+                    .map_err(io::Error::other)?;
+                line.insert_str(0, &timestamp);
+                self.finish_line(line);
+            }
+            writer.write_all(line.as_bytes())?;
+            writer.flush()
+        })
+    }
+
+    /// Writes the summary of records suppressed by the rate limit, with the
+    /// level, target and caller of the record that follows it.
+    fn write_summary(&self, record: &Record, suppressed: u64) -> io::Result<()> {
+        self.write_log(
+            &Record::builder()
+                .metadata(record.metadata().clone())
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .args(format_args!(
+                    "suppressed {} similar messages",
+                    Grouped(suppressed)
+                ))
+                .build(),
+        )
+    }
 
-        writeln!(writer)?;
-        writer.flush()
+    /// Writes the summaries of rate limited keys that have no following
+    /// record to report them, with the level, target and caller of each
+    /// key's first suppressed record.
+    fn write_suppressed(&self, summaries: Vec<Suppressed>) {
+        for summary in summaries {
+            summary.with_record(|record| {
+                fallback_on_error(&self.config, record, |rec| {
+                    self.write_summary(rec, summary.count)
+                })
+            });
+        }
     }
 }
 
@@ -270,11 +751,27 @@ impl Log for TwygLogger {
             return; // Early exit (fern pattern)
         }
 
+        if let Some(limiter) = &self.rate_limiter {
+            let mut pruned = Vec::new();
+            let checked = limiter.check(record, self.config.clock.monotonic(), &mut pruned);
+            self.write_suppressed(pruned);
+            match checked {
+                None => return,
+                Some(0) => {}
+                Some(suppressed) => fallback_on_error(&self.config, record, |rec| {
+                    self.write_summary(rec, suppressed)
+                }),
+            }
+        }
+
         // Three-tiered error recovery: normal → stderr → panic (fern pattern)
-        fallback_on_error(record, |rec| self.write_log(rec));
+        fallback_on_error(&self.config, record, |rec| self.write_log(rec));
     }
 
     fn flush(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            self.write_suppressed(limiter.drain());
+        }
         let _ = self.output_lock().flush();
     }
 }
@@ -283,151 +780,381 @@ impl Log for TwygLogger {
 ///
 /// Marked #[inline(always)] to avoid overhead in hot path.
 #[inline(always)]
-fn fallback_on_error<F>(record: &Record, log_func: F)
+fn fallback_on_error<F>(config: &LoggerConfig, record: &Record, log_func: F)
 where
     F: FnOnce(&Record) -> io::Result<()>,
 {
     if let Err(error) = log_func(record) {
-        backup_to_stderr(record, &error);
+        backup_to_stderr(config, record, &error);
     }
 }
 
 /// Fallback to stderr if primary logging fails (fern pattern).
 ///
 /// Only panics if stderr also fails (catastrophic failure).
-fn backup_to_stderr(record: &Record, error: &io::Error) {
+fn backup_to_stderr(config: &LoggerConfig, record: &Record, error: &io::Error) {
+    let line = fallback_line(config, record, error);
     let stderr = io::stderr();
     let mut handle = stderr.lock();
 
-    let write_result = writeln!(
-        handle,
-        "[twyg error: {}] {} - {}",
-        error,
-        record.level(),
-        record.args()
-    );
-
-    if let Err(stderr_err) = write_result {
+    if let Err(stderr_err) = handle.write_all(line.as_bytes()) {
         panic!(
             "twyg: failed to write to stderr (err: {:?}), \
              failed to write to primary output (err: {:?}), \
-             log record: {:?}",
-            stderr_err, error, record
+             log record: {}",
+            stderr_err,
+            error,
+            line.trim_end()
         );
     }
 }
 
-// Key-Value Collector for structured logging
+/// Renders the line written to stderr when the primary output fails, with
+/// the message redacted and sanitised as it would have been.
+fn fallback_line(config: &LoggerConfig, record: &Record, error: &io::Error) -> String {
+    let mut line = format!("[twyg error: {}] {} - ", error, record.level());
+    let _ = config.write_message(&mut line, None, record);
+    line.push('\n');
+    line
+}
+
+// Key-value writer for structured logging
+
+/// Visitor that writes key-value pairs straight into the line buffer.
+///
+/// The caller column recorded by twyg's macros is skipped so that it is
+/// never printed as a structured field.
+struct KeyValueWriter<'a> {
+    out: &'a mut String,
+    config: &'a LoggerConfig,
+    written: usize,
+    /// Width keys are padded to in [`FieldLayout::Pretty`], or `None` for
+    /// inline pairs.
+    key_width: Option<usize>,
+}
 
-use log::kv::{Key, Value, VisitSource};
+impl<'kvs> VisitSource<'kvs> for KeyValueWriter<'_> {
+    fn visit_pair(
+        &mut self,
+        key: Key<'kvs>,
+        value: Value<'kvs>,
+    ) -> std::result::Result<(), log::kv::Error> {
+        let key = key.as_str();
+        if key == COLUMN_KEY {
+            return Ok(());
+        }
 
-/// Visitor for collecting key-value pairs from log records.
-struct KeyValueCollector {
-    pairs: Vec<(String, String)>,
+        match self.key_width {
+            None => self.write_inline(key, &value)?,
+            Some(width) => self.write_pretty(key, &value, width)?,
+        }
+        self.written += 1;
+        Ok(())
+    }
 }
 
-impl KeyValueCollector {
-    fn new() -> Self {
-        Self { pairs: Vec::new() }
+impl KeyValueWriter<'_> {
+    /// Writes a pair after the message or the previous pair.
+    fn write_inline(&mut self, key: &str, value: &Value) -> fmt::Result {
+        let config = self.config;
+        let separator = if self.written == 0 {
+            config.msg_separator.as_str()
+        } else {
+            config.pair_separator.as_str()
+        };
+        let colors = &config.colors;
+
+        // Key with its per-field color, value with brackets and its
+        // per-field or per-type color
+        write!(
+            self.out,
+            "{}{}{}",
+            separator,
+            config.paint(colors.attr_key_color(key), config.label(key)),
+            config.kv_separator
+        )?;
+        let value_color = colors.attr_value_color(key, ValueKind::of(value));
+        if let Some(text) = config.masked_text(key, value) {
+            return config.write_value(self.out, value_color, config.redactor.mask(&text), true);
+        }
+        #[cfg(feature = "kv_serde")]
+        if let Some(mut tree) = nested(value) {
+            if config.redactor.redact_tree(&mut tree) {
+                let value = Value::from_serde(&tree);
+                return config.write_value(self.out, value_color, value, true);
+            }
+        }
+        config.write_value(self.out, value_color, value, true)
     }
 
-    #[cfg(test)]
-    fn is_empty(&self) -> bool {
-        self.pairs.is_empty()
+    /// Writes a pair on its own line with its key padded to `width`,
+    /// expanding nested values into a tree.
+    fn write_pretty(&mut self, key: &str, value: &Value, width: usize) -> fmt::Result {
+        let config = self.config;
+        let colors = &config.colors;
+        let label = config.label(key);
+        let pad = width.saturating_sub(label.width());
+        let line_start = self.out.len() + 1;
+        write!(
+            self.out,
+            "\n{}{}{:pad$} =",
+            FIELD_INDENT,
+            config.paint(colors.attr_key_color(key), label),
+            ""
+        )?;
+
+        let value_color = colors.attr_value_color(key, ValueKind::of(value));
+        if let Some(text) = config.masked_text(key, value) {
+            self.out.push(' ');
+            return config.write_value(self.out, value_color, config.redactor.mask(&text), false);
+        }
+        #[cfg(feature = "kv_serde")]
+        if let Some(tree) = nested(value) {
+            return self.write_tree(key, &tree, FIELD_INDENT.len() + TREE_INDENT);
+        }
+        self.out.push(' ');
+        let value_start = self.out.len();
+        config.write_value(self.out, value_color, value, false)?;
+        self.indent_value(line_start, value_start)
     }
 
-    fn format_pairs(&self, config: &LoggerConfig) -> String {
-        if self.pairs.is_empty() {
-            return String::new();
+    /// Lines up the continuation lines of a value written from
+    /// `value_start` on under its first line, if enabled.
+    fn indent_value(&mut self, line_start: usize, value_start: usize) -> fmt::Result {
+        if !self.config.indents_continuations() || !self.out[value_start..].contains('\n') {
+            return Ok(());
+        }
+        let column = visible_width(&self.out[line_start..value_start]);
+        let text = self.out.split_off(value_start);
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                write!(self.out, "\n{:column$}", "")?;
+            }
+            self.out.push_str(line);
         }
+        Ok(())
+    }
 
-        let formatted = self
-            .pairs
-            .iter()
-            .map(|(k, v)| {
-                // Format key with config color
-                let key_colored = config
-                    .colors
-                    .attr_key
-                    .as_ref()
-                    .map(|c| c.apply(k, config.stream))
-                    .unwrap_or_else(|| k.clone());
-
-                // Format value with braces and config color
-                let value_with_braces = format!("{{{}}}", v);
-                let value_colored = config
-                    .colors
-                    .attr_value
-                    .as_ref()
-                    .map(|c| c.apply(&value_with_braces, config.stream))
-                    .unwrap_or(value_with_braces);
-
-                format!("{}={}", key_colored, value_colored)
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
+    /// Writes the entries of a nested value, one per line, indented by
+    /// `indent`. Values are coloured as the top-level field `key`.
+    #[cfg(feature = "kv_serde")]
+    fn write_tree(&mut self, key: &str, tree: &serde_json::Value, indent: usize) -> fmt::Result {
+        use serde_json::Value as Json;
+
+        let config = self.config;
+        let entries: Box<dyn Iterator<Item = (Option<&str>, &Json)>> = match tree {
+            Json::Object(map) => {
+                Box::new(map.iter().map(|(name, child)| (Some(name.as_str()), child)))
+            }
+            Json::Array(items) => Box::new(items.iter().map(|child| (None, child))),
+            _ => return Ok(()),
+        };
+        for (name, child) in entries {
+            let line_start = self.out.len() + 1;
+            write!(self.out, "\n{:indent$}", "")?;
+            match name {
+                Some(name) => write!(
+                    self.out,
+                    "{}:",
+                    config.paint(config.colors.attr_key.as_ref(), config.label(name))
+                )?,
+                None => self.out.push('-'),
+            }
+
+            let redacted = name.is_some_and(|name| config.redactor.redacts_key(name));
+            if !redacted && is_branch(child) {
+                self.write_tree(key, child, indent + TREE_INDENT)?;
+                continue;
+            }
+            self.out.push(' ');
+            let kind = match child {
+                Json::Bool(_) => ValueKind::Bool,
+                Json::Number(_) => ValueKind::Number,
+                Json::String(_) => ValueKind::String,
+                _ => ValueKind::Other,
+            };
+            let color = config.colors.attr_value_color(key, kind);
+            let text = match child {
+                Json::String(text) => Cow::Borrowed(text.as_str()),
+                other => Cow::Owned(other.to_string()),
+            };
+            let value_start = self.out.len();
+            if redacted {
+                config.write_value(self.out, color, config.redactor.mask(&text), false)?;
+            } else {
+                config.write_value(self.out, color, text, false)?;
+            }
+            self.indent_value(line_start, value_start)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns a value captured with serde as JSON, when it's a non-empty map
+/// or sequence.
+#[cfg(feature = "kv_serde")]
+fn nested(value: &Value) -> Option<serde_json::Value> {
+    if ValueKind::of(value) != ValueKind::Other {
+        return None;
+    }
+    serde_json::to_value(value).ok().filter(is_branch)
+}
 
-        format!("{}{}", config.msg_separator, formatted)
+#[cfg(feature = "kv_serde")]
+fn is_branch(tree: &serde_json::Value) -> bool {
+    match tree {
+        serde_json::Value::Object(map) => !map.is_empty(),
+        serde_json::Value::Array(items) => !items.is_empty(),
+        _ => false,
     }
 }
 
-impl<'kvs> VisitSource<'kvs> for KeyValueCollector {
+/// Visitor that collects a record's key-value pairs.
+struct FieldCollector<'kvs>(Vec<(Key<'kvs>, Value<'kvs>)>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector<'kvs> {
     fn visit_pair(
         &mut self,
         key: Key<'kvs>,
         value: Value<'kvs>,
     ) -> std::result::Result<(), log::kv::Error> {
-        // Convert key and value to strings
-        self.pairs.push((key.to_string(), value.to_string()));
+        if key.as_str() != COLUMN_KEY {
+            self.0.push((key, value));
+        }
         Ok(())
     }
 }
 
+/// Returns the caller column recorded by twyg's macros, if any.
+fn caller_column(record: &Record) -> Option<u32> {
+    let column = record.key_values().get(Key::from_str(COLUMN_KEY))?;
+    column.to_u64().and_then(|c| u32::try_from(c).ok())
+}
+
 // Helper functions
 
 fn opt_str_or_placeholder(x: Option<&str>) -> &str {
     x.unwrap_or("??")
 }
 
-fn opt_u32_or_placeholder(x: Option<u32>) -> std::borrow::Cow<'static, str> {
-    match x {
-        None => std::borrow::Cow::Borrowed("??"),
-        Some(val) => std::borrow::Cow::Owned(val.to_string()),
+fn opt_u32_or_placeholder(x: Option<u32>) -> OptU32 {
+    OptU32(x)
+}
+
+/// A number, or "??" when missing.
+struct OptU32(Option<u32>);
+
+impl fmt::Display for OptU32 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(val) => val.fmt(f),
+            None => f.write_str("??"),
+        }
+    }
+}
+
+/// Text padded to `width` characters, after truncating it to that width
+/// when a truncate side is set.
+struct Fit<'a> {
+    text: Cow<'a, str>,
+    width: usize,
+    pad_side: PadSide,
+    truncate_side: Option<TruncateSide>,
+}
+
+impl fmt::Display for Fit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self.truncate_side {
+            Some(side) => truncate_str(&self.text, self.width, side),
+            None => &self.text,
+        };
+        // Pad by display width, so wide characters keep columns aligned
+        let fill = self.width.saturating_sub(text.width());
+        match self.pad_side {
+            PadSide::Left => write!(f, "{:fill$}{}", "", text),
+            PadSide::Right => write!(f, "{}{:fill$}", text, ""),
+        }
+    }
+}
+
+/// Pad a string to specified width
+fn pad_str<'a>(text: impl Into<Cow<'a, str>>, amount: usize, side: PadSide) -> Fit<'a> {
+    Fit {
+        text: text.into(),
+        width: amount,
+        pad_side: side,
+        truncate_side: None,
     }
 }
 
-/// Pad a level string to specified width
-fn pad_level(level: &str, amount: usize, side: PadSide) -> String {
+/// Fit a string to exactly `width` columns, truncating then padding
+fn fit_width<'a>(
+    text: impl Into<Cow<'a, str>>,
+    width: usize,
+    pad_side: PadSide,
+    truncate_side: TruncateSide,
+) -> Fit<'a> {
+    Fit {
+        text: text.into(),
+        width,
+        pad_side,
+        truncate_side: Some(truncate_side),
+    }
+}
+
+/// Keep at most `width` columns, dropping characters from `side`
+fn truncate_str(text: &str, width: usize, side: TruncateSide) -> &str {
+    if text.width() <= width {
+        return text;
+    }
+
+    // Keep whole characters while they fit; a wide character that would
+    // straddle the edge is dropped and left to padding
+    let mut used = 0;
     match side {
-        PadSide::Left => format!("{:>width$}", level, width = amount),
-        PadSide::Right => format!("{:<width$}", level, width = amount),
+        TruncateSide::Left => {
+            let start = text
+                .char_indices()
+                .rev()
+                .take_while(|&(_, c)| {
+                    used += c.width().unwrap_or(0);
+                    used <= width
+                })
+                .last()
+                .map_or(text.len(), |(i, _)| i);
+            &text[start..]
+        }
+        TruncateSide::Right => {
+            let end = text
+                .char_indices()
+                .find(|&(_, c)| {
+                    used += c.width().unwrap_or(0);
+                    used > width
+                })
+                .map_or(text.len(), |(i, _)| i);
+            &text[..end]
+        }
     }
 }
 
-/// Format a level with optional padding and config-driven colors
-fn format_level(
+/// Format a level's label with optional padding and config-driven colors
+fn format_level<'a>(
     level: Level,
-    colors: &Colors,
+    labels: &'a LevelLabels,
+    colors: &'a Colors,
     pad: bool,
     pad_amount: usize,
     pad_side: PadSide,
-    stream: Stream,
-) -> String {
-    let level_str = level.to_string();
-
-    // Apply padding if enabled
-    let padded = if pad {
-        pad_level(&level_str, pad_amount, pad_side)
-    } else {
-        level_str
-    };
-
-    // Apply color from config
-    if let Some(color) = colors.level_color(level) {
-        color.apply(&padded, stream)
-    } else {
-        padded
-    }
+    stream: Option<Stream>,
+) -> Painted<'a, Fit<'a>> {
+    let width = if pad { pad_amount } else { 0 };
+
+    // Color from config, unless the sink is uncoloured
+    Painted::new(
+        pad_str(labels.label(level), width, pad_side),
+        colors.level_color(level),
+        stream,
+    )
 }
 
 // Public API - Logger struct for backwards compatibility
@@ -447,6 +1174,16 @@ impl Logger {
     ///
     /// This replaces the previous dispatch() method.
     pub fn dispatch(&self) -> Result<()> {
+        // Options deserialized from config bypass OptsBuilder validation
+        self.opts.timezone().resolve()?;
+        self.opts.redaction().compile()?;
+        if let Some(limit) = self.opts.rate_limit() {
+            limit.compile()?;
+        }
+        validate_len_limit("max_value_len", self.opts.max_value_len())?;
+        validate_len_limit("max_message_len", self.opts.max_message_len())?;
+        validate_len_limit("max_line_len", self.opts.max_line_len())?;
+
         // Create output writer based on opts
         let output_writer = match self.opts.output() {
             Output::Stdout => OutputWriter::Stdout(io::stdout()),
@@ -472,6 +1209,8 @@ impl Logger {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use super::*;
     use crate::opts::OptsBuilder;
 
@@ -535,13 +1274,13 @@ mod tests {
 
     #[test]
     fn test_opt_u32_or_placeholder_with_some() {
-        let result = opt_u32_or_placeholder(Some(42));
+        let result = opt_u32_or_placeholder(Some(42)).to_string();
         assert_eq!(result, "42");
     }
 
     #[test]
     fn test_opt_u32_or_placeholder_with_none() {
-        let result = opt_u32_or_placeholder(None);
+        let result = opt_u32_or_placeholder(None).to_string();
         assert_eq!(result, "??");
     }
 
@@ -550,12 +1289,14 @@ mod tests {
         let colors = Colors::default();
         let formatted = format_level(
             Level::Info,
+            &LevelLabels::default(),
             &colors,
             false,
             5,
             PadSide::Right,
-            Stream::Stdout,
-        );
+            Some(Stream::Stdout),
+        )
+        .to_string();
         assert!(formatted.contains("INFO") || formatted.contains("info"));
     }
 
@@ -564,12 +1305,14 @@ mod tests {
         let colors = Colors::default();
         let formatted = format_level(
             Level::Info,
+            &LevelLabels::default(),
             &colors,
             true,
             7,
             PadSide::Right,
-            Stream::Stdout,
-        );
+            Some(Stream::Stdout),
+        )
+        .to_string();
         assert!(formatted.contains("INFO") || formatted.contains("info"));
         // With right padding, "INFO" becomes "INFO   " (7 chars total)
     }
@@ -577,121 +1320,427 @@ mod tests {
     #[test]
     fn test_format_level_with_padding_left() {
         let colors = Colors::default();
-        let formatted = format_level(Level::Warn, &colors, true, 7, PadSide::Left, Stream::Stdout);
+        let formatted = format_level(
+            Level::Warn,
+            &LevelLabels::default(),
+            &colors,
+            true,
+            7,
+            PadSide::Left,
+            Some(Stream::Stdout),
+        )
+        .to_string();
         assert!(formatted.contains("WARN") || formatted.contains("warn"));
         // With left padding, "WARN" becomes "   WARN" (7 chars total)
     }
 
     #[test]
-    fn test_pad_level() {
-        assert_eq!(pad_level("INFO", 5, PadSide::Right), "INFO ");
-        assert_eq!(pad_level("INFO", 5, PadSide::Left), " INFO");
-        assert_eq!(pad_level("ERROR", 7, PadSide::Right), "ERROR  ");
-        assert_eq!(pad_level("WARN", 5, PadSide::Left), " WARN");
+    fn test_pad_str() {
+        assert_eq!(pad_str("INFO", 5, PadSide::Right).to_string(), "INFO ");
+        assert_eq!(pad_str("INFO", 5, PadSide::Left).to_string(), " INFO");
+        assert_eq!(pad_str("ERROR", 7, PadSide::Right).to_string(), "ERROR  ");
+        assert_eq!(pad_str("WARN", 5, PadSide::Left).to_string(), " WARN");
     }
 
     #[test]
-    fn test_format_level_all_levels() {
-        let colors = Colors::default();
+    fn test_pad_str_display_width() {
+        assert_eq!(pad_str("注意", 6, PadSide::Right).to_string(), "注意  ");
+        assert_eq!(pad_str("🔥 HOT", 8, PadSide::Left).to_string(), "  🔥 HOT");
+        assert_eq!(pad_str("▶", 3, PadSide::Right).to_string(), "▶  ");
+    }
 
-        let error = format_level(
-            Level::Error,
-            &colors,
-            false,
-            5,
-            PadSide::Right,
-            Stream::Stdout,
+    #[test]
+    fn test_fit_width() {
+        let target = "myapp::billing";
+        assert_eq!(
+            fit_width(target, 16, PadSide::Right, TruncateSide::Left).to_string(),
+            "myapp::billing  "
         );
-        assert!(error.contains("ERROR") || error.contains("error"));
-
-        let warn = format_level(
-            Level::Warn,
-            &colors,
-            false,
-            5,
-            PadSide::Right,
-            Stream::Stdout,
+        assert_eq!(
+            fit_width(target, 16, PadSide::Left, TruncateSide::Left).to_string(),
+            "  myapp::billing"
         );
-        assert!(warn.contains("WARN") || warn.contains("warn"));
-
-        let info = format_level(
-            Level::Info,
-            &colors,
-            false,
-            5,
-            PadSide::Right,
-            Stream::Stdout,
+        assert_eq!(
+            fit_width(target, 9, PadSide::Right, TruncateSide::Left).to_string(),
+            "::billing"
         );
-        assert!(info.contains("INFO") || info.contains("info"));
-
-        let debug = format_level(
-            Level::Debug,
-            &colors,
-            false,
-            5,
-            PadSide::Right,
-            Stream::Stdout,
+        assert_eq!(
+            fit_width(target, 9, PadSide::Right, TruncateSide::Right).to_string(),
+            "myapp::bi"
         );
-        assert!(debug.contains("DEBUG") || debug.contains("debug"));
-
-        let trace = format_level(
-            Level::Trace,
-            &colors,
-            false,
-            5,
-            PadSide::Right,
-            Stream::Stdout,
+        assert_eq!(
+            fit_width(target, 14, PadSide::Right, TruncateSide::Right).to_string(),
+            target
         );
-        assert!(trace.contains("TRACE") || trace.contains("trace"));
     }
 
     #[test]
-    fn test_kv_collector_empty() {
-        let collector = KeyValueCollector::new();
-        assert!(collector.is_empty());
-
+    fn test_fit_width_display_width() {
+        let target = "注文::処理";
+        assert_eq!(target.width(), 10);
+        assert_eq!(
+            fit_width(target, 12, PadSide::Right, TruncateSide::Left).to_string(),
+            "注文::処理  "
+        );
+        assert_eq!(
+            fit_width(target, 6, PadSide::Right, TruncateSide::Left).to_string(),
+            "::処理"
+        );
+        // A wide character that would straddle the edge is replaced by padding
+        assert_eq!(
+            fit_width(target, 5, PadSide::Right, TruncateSide::Left).to_string(),
+            ":処理"
+        );
+        assert_eq!(
+            fit_width(target, 3, PadSide::Right, TruncateSide::Right).to_string(),
+            "注 "
+        );
+        assert_eq!(
+            fit_width(target, 3, PadSide::Left, TruncateSide::Left).to_string(),
+            " 理"
+        );
+    }
+
+    #[test]
+    fn test_logger_config_zone() {
+        use crate::timestamp::Timezone;
+
         let opts = Opts::default();
-        let config = LoggerConfig {
-            stream: Stream::Stdout,
-            max_level: LevelFilter::from(opts.level()),
-            timestamp_format: opts.timestamp_format().clone(),
-            report_caller: opts.report_caller(),
-            pad_level: opts.pad_level(),
-            pad_amount: opts.pad_amount(),
-            pad_side: opts.pad_side(),
-            msg_separator: opts.msg_separator().to_string(),
-            arrow_char: opts.arrow_char().to_string(),
-            colors: opts.colors().clone(),
+        assert_eq!(LoggerConfig::new(&opts).zone, Zone::Local);
+
+        let opts = OptsBuilder::new()
+            .timezone(Timezone::Fixed("+05:30".to_string()))
+            .build()
+            .unwrap();
+        assert!(matches!(LoggerConfig::new(&opts).zone, Zone::Fixed(_)));
+
+        // RFC3339Utc always renders in UTC
+        let opts = OptsBuilder::new()
+            .timezone(Timezone::Fixed("+05:30".to_string()))
+            .timestamp_format(TSFormat::RFC3339Utc)
+            .build()
+            .unwrap();
+        assert_eq!(LoggerConfig::new(&opts).zone, Zone::Utc);
+    }
+
+    #[test]
+    fn test_twyg_logger_rfc3339_utc_timestamp() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .args(format_args!("utc"))
+            .build();
+
+        let contents = write_to_file(
+            "rfc3339-utc",
+            OptsBuilder::new().timestamp_format(TSFormat::RFC3339Utc),
+            &record,
+        );
+        let timestamp = contents.split(' ').next().unwrap();
+        assert_eq!(timestamp.len(), "2006-01-02T15:04:05Z".len());
+        assert!(timestamp.ends_with('Z'));
+        assert_eq!(&timestamp[10..11], "T");
+    }
+
+    #[test]
+    fn test_twyg_logger_without_timestamp() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .args(format_args!("no time"))
+            .build();
+
+        let contents = write_to_file(
+            "no-timestamp",
+            OptsBuilder::new().timestamp_format(TSFormat::None),
+            &record,
+        );
+        assert_eq!(contents, "INFO [test] ▶ no time\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_epoch_millis_timestamp() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .args(format_args!("epoch"))
+            .build();
+
+        let contents = write_to_file(
+            "epoch-millis",
+            OptsBuilder::new().timestamp_format(TSFormat::UnixMillis),
+            &record,
+        );
+        let (timestamp, rest) = contents.split_once(' ').unwrap();
+        assert_eq!(timestamp.len(), 13);
+        assert!(timestamp.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(rest, "INFO [test] ▶ epoch\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_elapsed_and_delta_timestamps() {
+        use std::time::Duration;
+
+        use crate::clock::ManualClock;
+
+        let path = std::env::temp_dir().join(format!(
+            "twyg-test-relative-time-{}.log",
+            std::process::id()
+        ));
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .args(format_args!("tick"))
+            .build();
+        let write = |format: TSFormat| {
+            let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+            let opts = OptsBuilder::new()
+                .timestamp_format(format)
+                .clock(clock.clone())
+                .output(Output::file(&path))
+                .build()
+                .unwrap();
+            let output = OutputWriter::File(BufWriter::new(File::create(&path).unwrap()));
+            let logger = TwygLogger::new(&opts, output);
+            clock.advance(Duration::from_millis(2_500));
+            logger.write_log(&record).unwrap();
+            clock.advance(Duration::from_millis(3));
+            logger.write_log(&record).unwrap();
+            std::fs::read_to_string(&path).unwrap()
         };
 
-        assert_eq!(collector.format_pairs(&config), "");
+        assert_eq!(
+            write(TSFormat::Elapsed),
+            "+2.500s INFO [test] ▶ tick\n+2.503s INFO [test] ▶ tick\n"
+        );
+        // The first delta is measured from setup, later ones from the previous record
+        assert_eq!(
+            write(TSFormat::Delta),
+            "Δ2.500s INFO [test] ▶ tick\nΔ3ms INFO [test] ▶ tick\n"
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_twyg_logger_timestamp_cache() {
+        use std::time::Duration;
+
+        use chrono::TimeZone;
+
+        use crate::clock::ManualClock;
+        use crate::timestamp::Timezone;
+
+        let clock = Arc::new(ManualClock::new(
+            chrono::Utc
+                .with_ymd_and_hms(2026, 1, 15, 14, 30, 52)
+                .unwrap(),
+        ));
+        let opts = OptsBuilder::new()
+            .clock(clock.clone())
+            .timezone(Timezone::Utc)
+            .build()
+            .unwrap();
+        let logger = TwygLogger::new(&opts, OutputWriter::Stderr(io::stderr()));
+
+        assert_eq!(timestamp(&logger), "2026-01-15 14:30:52 ");
+        let cached = TIMESTAMP_CACHE.take();
+        assert_eq!(
+            cached,
+            (
+                Some((logger.id, clock.now().timestamp())),
+                "2026-01-15 14:30:52 ".to_string()
+            )
+        );
+        TIMESTAMP_CACHE.set(cached);
+
+        // Reused within the second, re-rendered once it changes
+        clock.advance(Duration::from_millis(999));
+        assert_eq!(timestamp(&logger), "2026-01-15 14:30:52 ");
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(timestamp(&logger), "2026-01-15 14:30:53 ");
+
+        // Sub-second formats are never cached
+        let opts = OptsBuilder::new()
+            .clock(clock.clone())
+            .timezone(Timezone::Utc)
+            .timestamp_format(TSFormat::UnixMillis)
+            .build()
+            .unwrap();
+        let logger = TwygLogger::new(&opts, OutputWriter::Stderr(io::stderr()));
+        assert_eq!(timestamp(&logger), "1768487453000 ");
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(timestamp(&logger), "1768487453005 ");
+        let cached = TIMESTAMP_CACHE.take();
+        assert_ne!(cached.0.map(|(id, _)| id), Some(logger.id));
+
+        // Another logger's prefix on this thread is never reused
+        let opts = OptsBuilder::new()
+            .clock(clock.clone())
+            .timezone(Timezone::Utc)
+            .timestamp_format(TSFormat::TimeOnly)
+            .build()
+            .unwrap();
+        let other = TwygLogger::new(&opts, OutputWriter::Stderr(io::stderr()));
+        assert_eq!(timestamp(&other), "14:30:53 ");
+        let opts = OptsBuilder::new()
+            .clock(clock.clone())
+            .timezone(Timezone::Utc)
+            .build()
+            .unwrap();
+        let logger = TwygLogger::new(&opts, OutputWriter::Stderr(io::stderr()));
+        assert_eq!(timestamp(&logger), "2026-01-15 14:30:53 ");
+    }
+
+    #[test]
+    fn test_twyg_logger_manual_clock_golden_output() {
+        use chrono::TimeZone;
+
+        use crate::clock::ManualClock;
+        use crate::timestamp::Timezone;
+
+        let now = chrono::Utc
+            .with_ymd_and_hms(2026, 1, 15, 14, 30, 52)
+            .unwrap();
+        let record = log::Record::builder()
+            .level(Level::Warn)
+            .target("app::db")
+            .file(Some("src/db.rs"))
+            .line(Some(42))
+            .args(format_args!("slow query"))
+            .build();
+
+        let contents = write_to_file(
+            "golden",
+            OptsBuilder::new()
+                .clock(Arc::new(ManualClock::new(now)))
+                .timezone(Timezone::Utc)
+                .timestamp_format(TSFormat::RFC3339Millis)
+                .report_caller(true),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "2026-01-15T14:30:52.000+00:00 WARN [src/db.rs:42 app::db] ▶ slow query\n"
+        );
+    }
+
+    #[test]
+    fn test_logger_dispatch_rejects_invalid_timezone() {
+        let opts: Opts = toml::from_str(r#"timezone = { Fixed = "noon" }"#).unwrap();
+        let result = Logger::new(opts).dispatch();
+        assert!(matches!(
+            result,
+            Err(crate::error::TwygError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_logger_config_format_target() {
+        let opts = OptsBuilder::new()
+            .target_format(TargetFormat::Abbreviated)
+            .pad_target(true)
+            .target_pad_amount(12)
+            .build()
+            .unwrap();
+        let config = LoggerConfig::new(&opts);
+        assert_eq!(
+            config.format_target("myapp::billing::invoices").to_string(),
+            ":b::invoices"
+        );
+        assert_eq!(
+            config.format_target("myapp::db").to_string(),
+            "m::db       "
+        );
+
+        let unpadded = LoggerConfig::new(&Opts::default());
+        assert_eq!(
+            unpadded
+                .format_target("myapp::billing::invoices")
+                .to_string(),
+            "myapp::billing::invoices"
+        );
+    }
+
+    #[test]
+    fn test_format_level_all_levels() {
+        let colors = Colors::default();
+
+        let error = format_level(
+            Level::Error,
+            &LevelLabels::default(),
+            &colors,
+            false,
+            5,
+            PadSide::Right,
+            Some(Stream::Stdout),
+        )
+        .to_string();
+        assert!(error.contains("ERROR") || error.contains("error"));
+
+        let warn = format_level(
+            Level::Warn,
+            &LevelLabels::default(),
+            &colors,
+            false,
+            5,
+            PadSide::Right,
+            Some(Stream::Stdout),
+        )
+        .to_string();
+        assert!(warn.contains("WARN") || warn.contains("warn"));
+
+        let info = format_level(
+            Level::Info,
+            &LevelLabels::default(),
+            &colors,
+            false,
+            5,
+            PadSide::Right,
+            Some(Stream::Stdout),
+        )
+        .to_string();
+        assert!(info.contains("INFO") || info.contains("info"));
+
+        let debug = format_level(
+            Level::Debug,
+            &LevelLabels::default(),
+            &colors,
+            false,
+            5,
+            PadSide::Right,
+            Some(Stream::Stdout),
+        )
+        .to_string();
+        assert!(debug.contains("DEBUG") || debug.contains("debug"));
+
+        let trace = format_level(
+            Level::Trace,
+            &LevelLabels::default(),
+            &colors,
+            false,
+            5,
+            PadSide::Right,
+            Some(Stream::Stdout),
+        )
+        .to_string();
+        assert!(trace.contains("TRACE") || trace.contains("trace"));
     }
 
     #[test]
-    fn test_kv_collector_format_pairs() {
-        let mut collector = KeyValueCollector::new();
-        collector
-            .pairs
-            .push(("user".to_string(), "alice".to_string()));
-        collector
-            .pairs
-            .push(("action".to_string(), "login".to_string()));
+    fn test_write_key_values_empty() {
+        let opts = Opts::default();
+        let config = LoggerConfig::new(&opts);
+
+        let none: [(&str, &str); 0] = [];
+        assert_eq!(key_values(&config, &none), "");
+    }
 
+    #[test]
+    fn test_write_key_values_pairs() {
         let opts = Opts::default();
-        let config = LoggerConfig {
-            stream: Stream::Stdout,
-            max_level: LevelFilter::from(opts.level()),
-            timestamp_format: opts.timestamp_format().clone(),
-            report_caller: opts.report_caller(),
-            pad_level: opts.pad_level(),
-            pad_amount: opts.pad_amount(),
-            pad_side: opts.pad_side(),
-            msg_separator: opts.msg_separator().to_string(),
-            arrow_char: opts.arrow_char().to_string(),
-            colors: opts.colors().clone(),
-        };
+        let config = LoggerConfig::new(&opts);
 
-        let formatted = collector.format_pairs(&config);
+        let formatted = key_values(&config, &[("user", "alice"), ("action", "login")]);
         // Check structure (color codes may be present, so check key parts)
         assert!(formatted.contains("user"));
         assert!(formatted.contains("alice"));
@@ -705,27 +1754,11 @@ mod tests {
     }
 
     #[test]
-    fn test_kv_collector_single_pair() {
-        let mut collector = KeyValueCollector::new();
-        collector
-            .pairs
-            .push(("key".to_string(), "value".to_string()));
-
+    fn test_write_key_values_single_pair() {
         let opts = Opts::default();
-        let config = LoggerConfig {
-            stream: Stream::Stdout,
-            max_level: LevelFilter::from(opts.level()),
-            timestamp_format: opts.timestamp_format().clone(),
-            report_caller: opts.report_caller(),
-            pad_level: opts.pad_level(),
-            pad_amount: opts.pad_amount(),
-            pad_side: opts.pad_side(),
-            msg_separator: opts.msg_separator().to_string(),
-            arrow_char: opts.arrow_char().to_string(),
-            colors: opts.colors().clone(),
-        };
+        let config = LoggerConfig::new(&opts);
 
-        let formatted = collector.format_pairs(&config);
+        let formatted = key_values(&config, &[("key", "value")]);
         // Check key components (color codes may be included)
         assert!(formatted.contains("key"));
         assert!(formatted.contains("value"));
@@ -739,8 +1772,8 @@ mod tests {
     fn test_output_writer_stdout() {
         let mut writer = OutputWriter::Stdout(io::stdout());
 
-        // Test write_fmt
-        let result = writer.write_fmt(format_args!("test"));
+        // Test write_all
+        let result = writer.write_all(b"test");
         assert!(result.is_ok());
 
         // Test flush
@@ -752,8 +1785,8 @@ mod tests {
     fn test_output_writer_stderr() {
         let mut writer = OutputWriter::Stderr(io::stderr());
 
-        // Test write_fmt
-        let result = writer.write_fmt(format_args!("test"));
+        // Test write_all
+        let result = writer.write_all(b"test");
         assert!(result.is_ok());
 
         // Test flush
@@ -806,17 +1839,20 @@ mod tests {
             target: None,
             attr_key: None,
             attr_value: None,
+            ..Colors::default()
         };
 
         // Should return uncolored level string
         let formatted = format_level(
             Level::Info,
+            &LevelLabels::default(),
             &empty_colors,
             false,
             5,
             PadSide::Right,
-            Stream::Stdout,
-        );
+            Some(Stream::Stdout),
+        )
+        .to_string();
         assert_eq!(formatted, "INFO");
     }
 
@@ -838,93 +1874,88 @@ mod tests {
             target: None,
             attr_key: None,
             attr_value: None,
+            ..Colors::default()
         };
 
         // With padding but no color
         let formatted = format_level(
             Level::Warn,
+            &LevelLabels::default(),
             &empty_colors,
             true,
             7,
             PadSide::Left,
-            Stream::Stdout,
-        );
+            Some(Stream::Stdout),
+        )
+        .to_string();
         assert_eq!(formatted, "   WARN");
     }
 
     #[test]
-    fn test_kv_collector_visit_pair() {
+    fn test_key_value_writer_visit_pair() {
         use log::kv::{Key, Value};
 
-        let mut collector = KeyValueCollector::new();
+        let config = LoggerConfig::new(&Opts::default());
+        let mut out = String::new();
+        let mut writer = KeyValueWriter {
+            out: &mut out,
+            config: &config,
+            written: 0,
+            key_width: None,
+        };
 
         // Test visit_pair method
         let key = Key::from_str("test_key");
         let value = Value::from_debug(&42);
 
-        let result = collector.visit_pair(key, value);
+        let result = writer.visit_pair(key, value);
         assert!(result.is_ok());
-        assert!(!collector.is_empty());
-        assert_eq!(collector.pairs.len(), 1);
-        assert_eq!(collector.pairs[0].0, "test_key");
+        assert_eq!(writer.written, 1);
+        assert_eq!(out, ": test_key={42}");
     }
 
     #[test]
-    fn test_kv_collector_multiple_visits() {
+    fn test_key_value_writer_multiple_visits() {
         use log::kv::{Key, Value};
 
-        let mut collector = KeyValueCollector::new();
+        let config = LoggerConfig::new(&Opts::default());
+        let mut out = String::new();
+        let mut writer = KeyValueWriter {
+            out: &mut out,
+            config: &config,
+            written: 0,
+            key_width: None,
+        };
 
         // Add multiple pairs
-        collector
+        writer
             .visit_pair(Key::from_str("key1"), Value::from_debug(&"value1"))
             .unwrap();
-        collector
+        writer
             .visit_pair(Key::from_str("key2"), Value::from_debug(&123))
             .unwrap();
-        collector
+        writer
             .visit_pair(Key::from_str("key3"), Value::from_debug(&true))
             .unwrap();
 
-        assert_eq!(collector.pairs.len(), 3);
-        assert_eq!(collector.pairs[0].0, "key1");
-        assert_eq!(collector.pairs[1].0, "key2");
-        assert_eq!(collector.pairs[2].0, "key3");
+        assert_eq!(writer.written, 3);
+        assert_eq!(out, r#": key1={"value1"}, key2={123}, key3={true}"#);
     }
 
     #[test]
-    fn test_kv_collector_format_with_custom_separator() {
-        let mut collector = KeyValueCollector::new();
-        collector
-            .pairs
-            .push(("user".to_string(), "bob".to_string()));
-
+    fn test_write_key_values_with_custom_separator() {
         let opts = OptsBuilder::new().msg_separator(" | ").build().unwrap();
 
-        let config = LoggerConfig {
-            stream: Stream::Stdout,
-            max_level: LevelFilter::from(opts.level()),
-            timestamp_format: opts.timestamp_format().clone(),
-            report_caller: opts.report_caller(),
-            pad_level: opts.pad_level(),
-            pad_amount: opts.pad_amount(),
-            pad_side: opts.pad_side(),
-            msg_separator: opts.msg_separator().to_string(),
-            arrow_char: opts.arrow_char().to_string(),
-            colors: opts.colors().clone(),
-        };
+        let config = LoggerConfig::new(&opts);
 
-        let formatted = collector.format_pairs(&config);
+        let formatted = key_values(&config, &[("user", "bob")]);
         assert!(formatted.starts_with(" | "));
     }
 
     #[test]
-    fn test_kv_collector_format_with_none_colors() {
+    fn test_write_key_values_with_none_colors() {
         use crate::color::Colors;
 
-        let mut collector = KeyValueCollector::new();
-        collector.pairs.push(("key".to_string(), "val".to_string()));
-
         let empty_colors = Colors {
             timestamp: None,
             level_trace: None,
@@ -939,22 +1970,17 @@ mod tests {
             target: None,
             attr_key: None,
             attr_value: None,
+            ..Colors::default()
         };
 
-        let config = LoggerConfig {
-            stream: Stream::Stdout,
-            max_level: LevelFilter::Info,
-            timestamp_format: TSFormat::default(),
-            report_caller: false,
-            pad_level: false,
-            pad_amount: 5,
-            pad_side: PadSide::Right,
-            msg_separator: ": ".to_string(),
-            arrow_char: "▶".to_string(),
-            colors: empty_colors,
-        };
+        let opts = OptsBuilder::new()
+            .coloured(true)
+            .colors(empty_colors)
+            .build()
+            .unwrap();
+        let config = LoggerConfig::new(&opts);
 
-        let formatted = collector.format_pairs(&config);
+        let formatted = key_values(&config, &[("key", "val")]);
         // Without colors, should still have structure
         assert!(formatted.contains("key={val}"));
     }
@@ -972,22 +1998,26 @@ mod tests {
         // Test with Stream::Stderr instead of Stdout
         let error = format_level(
             Level::Error,
+            &LevelLabels::default(),
             &colors,
             false,
             5,
             PadSide::Right,
-            Stream::Stderr,
-        );
+            Some(Stream::Stderr),
+        )
+        .to_string();
         assert!(error.contains("ERROR") || error.contains("error"));
 
         let trace = format_level(
             Level::Trace,
+            &LevelLabels::default(),
             &colors,
             false,
             5,
             PadSide::Right,
-            Stream::Stderr,
-        );
+            Some(Stream::Stderr),
+        )
+        .to_string();
         assert!(trace.contains("TRACE") || trace.contains("trace"));
     }
 
@@ -1144,6 +2174,7 @@ mod tests {
             target: None,
             attr_key: None,
             attr_value: None,
+            ..Colors::default()
         };
 
         let opts = OptsBuilder::new()
@@ -1175,7 +2206,7 @@ mod tests {
 
         // Test that output_lock() works
         let mut lock = logger.output_lock();
-        let result = lock.write_fmt(format_args!("test"));
+        let result = lock.write_all(b"test");
         assert!(result.is_ok());
     }
 
@@ -1205,4 +2236,1379 @@ mod tests {
             assert!(result.is_ok());
         }
     }
+
+    #[test]
+    fn test_logger_config_colour_per_sink() {
+        let stdout = OptsBuilder::new().coloured(true).build().unwrap();
+        assert!(LoggerConfig::new(&stdout).stream.is_some());
+
+        let file = OptsBuilder::new()
+            .coloured(true)
+            .output(Output::file("/tmp/twyg-test.log"))
+            .build()
+            .unwrap();
+        assert!(LoggerConfig::new(&file).stream.is_none());
+
+        let coloured_file = OptsBuilder::new()
+            .coloured(true)
+            .file_coloured(true)
+            .output(Output::file("/tmp/twyg-test.log"))
+            .build()
+            .unwrap();
+        assert!(LoggerConfig::new(&coloured_file).stream.is_some());
+
+        let uncoloured_file = OptsBuilder::new()
+            .file_coloured(true)
+            .output(Output::file("/tmp/twyg-test.log"))
+            .build()
+            .unwrap();
+        assert!(LoggerConfig::new(&uncoloured_file).stream.is_none());
+    }
+
+    #[test]
+    fn test_format_level_uncoloured_sink() {
+        let colors = Colors::default();
+        let labels = LevelLabels::default();
+        let formatted = format_level(
+            Level::Error,
+            &labels,
+            &colors,
+            false,
+            5,
+            PadSide::Right,
+            None,
+        );
+        assert_eq!(formatted.to_string(), "ERROR");
+    }
+
+    #[test]
+    fn test_format_level_labels() {
+        let colors = Colors::default();
+        let labels = LevelLabels::Short;
+        let formatted = format_level(Level::Warn, &labels, &colors, true, 3, PadSide::Right, None);
+        assert_eq!(formatted.to_string(), "WRN");
+
+        // Icons pad by display width, so "⚠️" and "🔥" fill the same columns
+        let labels = LevelLabels::Icons;
+        let warn = format_level(Level::Warn, &labels, &colors, true, 3, PadSide::Right, None);
+        let error = format_level(
+            Level::Error,
+            &labels,
+            &colors,
+            true,
+            3,
+            PadSide::Right,
+            None,
+        );
+        assert_eq!(warn.to_string(), "⚠\u{fe0f} ");
+        assert_eq!(error.to_string(), "🔥 ");
+    }
+
+    #[test]
+    fn test_twyg_logger_level_labels_pad_to_widest() {
+        use crate::level::CustomLevelLabels;
+
+        let record = |level| {
+            log::Record::builder()
+                .level(level)
+                .target("app")
+                .args(format_args!("ready"))
+                .build()
+        };
+        let opts = |labels| {
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .pad_level(true)
+                .level_labels(labels)
+        };
+        let custom = || {
+            LevelLabels::Custom(CustomLevelLabels {
+                warn: "WARNING".to_string(),
+                ..CustomLevelLabels::default()
+            })
+        };
+
+        // Levels are padded to the widest label
+        let contents = write_to_file("labels-info", opts(custom()), &record(Level::Info));
+        assert_eq!(contents, "INFO    [app] ▶ ready\n");
+        let contents = write_to_file("labels-warn", opts(custom()), &record(Level::Warn));
+        assert_eq!(contents, "WARNING [app] ▶ ready\n");
+        let contents = write_to_file(
+            "labels-short",
+            opts(LevelLabels::Short),
+            &record(Level::Warn),
+        );
+        assert_eq!(contents, "WRN [app] ▶ ready\n");
+        let contents = write_to_file(
+            "labels-letter",
+            opts(LevelLabels::Letter),
+            &record(Level::Warn),
+        );
+        assert_eq!(contents, "W [app] ▶ ready\n");
+
+        // A wider pad_amount set explicitly is kept; a narrower one widens
+        let contents = write_to_file(
+            "labels-pad-amount",
+            opts(LevelLabels::Short).pad_amount(5),
+            &record(Level::Warn),
+        );
+        assert_eq!(contents, "WRN   [app] ▶ ready\n");
+        let contents = write_to_file(
+            "labels-pad-amount-narrow",
+            opts(custom()).pad_amount(2),
+            &record(Level::Warn),
+        );
+        assert_eq!(contents, "WARNING [app] ▶ ready\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_file_output_is_plain() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .file(Some("test.rs"))
+            .line(Some(7))
+            .key_values(&[("user", "alice")])
+            .args(format_args!("to a file"))
+            .build();
+        owo_colors::set_override(true);
+
+        let contents = write_to_file(
+            "plain",
+            OptsBuilder::new().coloured(true).report_caller(true),
+            &record,
+        );
+        assert!(contents.contains("INFO [test.rs:7 test] ▶ to a file: user={alice}"));
+        assert!(!contents.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_write_key_values_skips_column() {
+        let kvs = [
+            (COLUMN_KEY, log::kv::Value::from(17u32)),
+            ("user", log::kv::Value::from("alice")),
+        ];
+        let record = log::Record::builder().key_values(&kvs).build();
+        assert_eq!(caller_column(&record), Some(17));
+
+        let opts = OptsBuilder::new().coloured(false).build().unwrap();
+        let config = LoggerConfig::new(&opts);
+        assert_eq!(key_values(&config, &kvs), ": user={alice}");
+    }
+
+    #[test]
+    fn test_with_line_buffer_reuse() {
+        // The buffer is cleared but keeps its capacity between records
+        with_line_buffer(|line| line.push_str("first record"));
+        with_line_buffer(|line| {
+            assert!(line.is_empty());
+            assert!(line.capacity() >= "first record".len());
+        });
+
+        // A record rendered while another is in progress gets its own buffer
+        with_line_buffer(|outer| {
+            outer.push_str("outer");
+            with_line_buffer(|inner| assert!(inner.is_empty()));
+            assert_eq!(outer, "outer");
+        });
+
+        // Oversized buffers are dropped
+        with_line_buffer(|line| line.reserve(LINE_BUFFER_RETAIN + 1));
+        with_line_buffer(|line| assert!(line.capacity() <= LINE_BUFFER_RETAIN));
+    }
+
+    #[test]
+    fn test_twyg_logger_log_while_rendering() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        /// Logs through the same logger when displayed.
+        struct Nested(Arc<TwygLogger>);
+
+        impl fmt::Display for Nested {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.log(
+                    &log::Record::builder()
+                        .level(Level::Info)
+                        .target("inner")
+                        .args(format_args!("inner"))
+                        .build(),
+                );
+                f.write_str("outer")
+            }
+        }
+
+        let path =
+            std::env::temp_dir().join(format!("twyg-test-reentrant-{}.log", std::process::id()));
+        let opts = OptsBuilder::new()
+            .level(LogLevel::Info)
+            .timestamp_format(TSFormat::Delta)
+            .output(Output::file(&path))
+            .build()
+            .unwrap();
+        let output = OutputWriter::File(BufWriter::new(File::create(&path).unwrap()));
+        let logger = Arc::new(TwygLogger::new(&opts, output));
+
+        // A deadlock would hang the logging thread, not the test
+        let (done, finished) = mpsc::channel();
+        std::thread::spawn(move || {
+            logger.log(
+                &log::Record::builder()
+                    .level(Level::Info)
+                    .target("outer")
+                    .args(format_args!("{}", Nested(Arc::clone(&logger))))
+                    .build(),
+            );
+            let _ = done.send(());
+        });
+        finished
+            .recv_timeout(Duration::from_secs(10))
+            .expect("logging while rendering a record deadlocked");
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<_> = contents
+            .lines()
+            .map(|line| line.split_once(' ').unwrap().1)
+            .collect();
+        assert_eq!(lines, ["INFO [inner] ▶ inner", "INFO [outer] ▶ outer"]);
+    }
+
+    fn timestamp(logger: &TwygLogger) -> String {
+        let mut out = String::new();
+        logger.write_timestamp(&mut out).unwrap();
+        out
+    }
+
+    fn key_values(config: &LoggerConfig, source: &dyn Source) -> String {
+        let mut out = String::new();
+        config.write_key_values(&mut out, source);
+        out
+    }
+
+    fn write_to_file(name: &str, opts: OptsBuilder, record: &Record) -> String {
+        let path =
+            std::env::temp_dir().join(format!("twyg-test-{}-{}.log", name, std::process::id()));
+        let opts = opts.output(Output::file(&path)).build().unwrap();
+        let output = OutputWriter::File(BufWriter::new(File::create(&path).unwrap()));
+        let logger = TwygLogger::new(&opts, output);
+        logger.write_log(record).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        contents
+    }
+
+    #[test]
+    fn test_twyg_logger_process_fields() {
+        use crate::process::{Hostname, ProcessInfo};
+
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .key_values(&[("user", "alice")])
+            .args(format_args!("request"))
+            .build();
+        let process = ProcessInfo {
+            service: Some("billing-api".to_string()),
+            version: Some("1.4.2".to_string()),
+            environment: Some("staging".to_string()),
+            hostname: Hostname::Fixed("web-01".to_string()),
+            pid: true,
+        };
+
+        let contents = write_to_file(
+            "process",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .report_process(true)
+                .process(process.clone()),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            format!(
+                "INFO [test] ▶ request: user={{alice}}, service={{billing-api}}, \
+                 version={{1.4.2}}, env={{staging}}, host={{web-01}}, pid={{{}}}\n",
+                std::process::id()
+            )
+        );
+
+        // Configured but not reported
+        let contents = write_to_file(
+            "process-hidden",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .process(process),
+            &record,
+        );
+        assert_eq!(contents, "INFO [test] ▶ request: user={alice}\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_process_fields_give_way() {
+        use crate::process::{Hostname, ProcessInfo};
+
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .key_values(&[("host", "replica-2"), ("pid", "1")])
+            .args(format_args!("request"))
+            .build();
+        let mut fields = BTreeMap::new();
+        fields.insert("version".to_string(), "canary".to_string());
+        let process = ProcessInfo {
+            service: Some("billing-api".to_string()),
+            version: Some("1.4.2".to_string()),
+            hostname: Hostname::Fixed("web-01".to_string()),
+            pid: true,
+            ..ProcessInfo::default()
+        };
+
+        let contents = write_to_file(
+            "process-dedup",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .fields(fields)
+                .report_process(true)
+                .process(process),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ request: host={replica-2}, pid={1}, version={canary}, \
+             service={billing-api}\n"
+        );
+    }
+
+    #[test]
+    fn test_collect_fields_keeps_each_key_once() {
+        use crate::process::{Hostname, ProcessInfo};
+
+        let opts = OptsBuilder::new()
+            .field("region", "eu")
+            .field("user", "static")
+            .report_process(true)
+            .process(ProcessInfo {
+                service: Some("api".to_string()),
+                hostname: Hostname::Fixed("web-01".to_string()),
+                ..ProcessInfo::default()
+            })
+            .build()
+            .unwrap();
+        let config = LoggerConfig::new(&opts);
+        let record = log::Record::builder()
+            .key_values(&[("host", "replica-2"), ("id", "7")])
+            .args(format_args!("request"))
+            .build();
+
+        let keys = context::with_fields([("step", "outer"), ("user", "alice")], || {
+            context::with_fields([("step", "inner"), ("region", "us")], || {
+                context::with_scoped_fields(|scoped| {
+                    config
+                        .collect_fields(record.key_values(), scoped)
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect::<Vec<_>>()
+                })
+            })
+        });
+        assert_eq!(
+            keys,
+            [
+                "host=replica-2",
+                "id=7",
+                "user=alice",
+                "step=inner",
+                "region=us",
+                "service=api"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_static_fields() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .key_values(&[("user", "alice"), ("deployment", "green")])
+            .args(format_args!("request"))
+            .build();
+
+        let contents = write_to_file(
+            "static-fields",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field("region", "us-east-1")
+                .field("deployment", "blue"),
+            &record,
+        );
+        // The record's own deployment wins over the static one
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ request: user={alice}, deployment={green}, region={us-east-1}\n"
+        );
+
+        let bare = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .args(format_args!("started"))
+            .build();
+        let contents = write_to_file(
+            "static-fields-only",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field("region", "us-east-1"),
+            &bare,
+        );
+        assert_eq!(contents, "INFO [test] ▶ started: region={us-east-1}\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_scoped_fields() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .key_values(&[("user", "alice")])
+            .args(format_args!("request"))
+            .build();
+        let opts = || {
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field("region", "us-east-1")
+                .field("step", "static")
+        };
+
+        // Record fields, then scoped fields, then static fields, each
+        // overriding the ones after it
+        let contents = crate::context::with_fields(
+            [("request_id", "r-7"), ("user", "bob"), ("step", "scoped")],
+            || write_to_file("scoped-fields", opts(), &record),
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ request: user={alice}, request_id={r-7}, step={scoped}, \
+             region={us-east-1}\n"
+        );
+
+        let contents = write_to_file("scoped-fields-after", opts(), &record);
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ request: user={alice}, region={us-east-1}, step={static}\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_redacts_fields() {
+        use crate::redact::{MaskStyle, Redaction};
+
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .key_values(&[
+                ("user", "alice"),
+                ("password", "hunter2"),
+                ("api_token", "tok_1234567890"),
+            ])
+            .args(format_args!("login"))
+            .build();
+        let redaction = |style| Redaction {
+            keys: vec!["password".to_string(), "*_token".to_string()],
+            style,
+            ..Redaction::default()
+        };
+
+        let contents = write_to_file(
+            "redact-full",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field("deploy_token", "secret")
+                .redaction(redaction(MaskStyle::Full)),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ login: user={alice}, password={***}, api_token={***}, \
+             deploy_token={***}\n"
+        );
+
+        let contents = write_to_file(
+            "redact-partial",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .redaction(redaction(MaskStyle::Partial)),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ login: user={alice}, password={***}, api_token={***7890}\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_redacts_debug_values_naming_a_key() {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct Cfg {
+            user: &'static str,
+            password: &'static str,
+        }
+
+        let cfg = Cfg {
+            user: "app",
+            password: "hunter2",
+        };
+        let contents = write_to_file(
+            "redact-inline-debug",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .redaction(crate::redact::Redaction {
+                    keys: vec!["password".to_string()],
+                    ..Default::default()
+                }),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("test")
+                .key_values(&[
+                    ("cfg", Value::from_debug(&cfg)),
+                    ("hosts", Value::from_debug(&["a", "b"])),
+                ])
+                .args(format_args!("loaded"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ loaded: cfg={***}, hosts={[\"a\", \"b\"]}\n"
+        );
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_twyg_logger_redacts_nested_fields_inline() {
+        let config = serde_json::json!({
+            "db": {"user": "app", "password": "hunter2"},
+            "port": 8080,
+        });
+        let contents = write_to_file(
+            "redact-inline-nested",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .redaction(crate::redact::Redaction {
+                    keys: vec!["password".to_string()],
+                    ..Default::default()
+                }),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("test")
+                .key_values(&[("config", Value::from_serde(&config))])
+                .args(format_args!("loaded"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ loaded: \
+             config={{\"db\": {\"password\": \"***\", \"user\": \"app\"}, \"port\": 8080}}\n"
+        );
+    }
+
+    #[test]
+    fn test_logger_dispatch_rejects_unusable_redaction_pattern() {
+        let opts: Opts = toml::from_str(
+            r#"
+            [redaction]
+            patterns = ["(unclosed"]
+            "#,
+        )
+        .unwrap();
+        let result = Logger::new(opts).dispatch();
+        assert!(matches!(
+            result,
+            Err(crate::error::TwygError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_logger_dispatch_rejects_empty_rate_limit() {
+        let opts: Opts = toml::from_str("rate_limit = { max_records = 0 }").unwrap();
+        let result = Logger::new(opts).dispatch();
+        assert!(matches!(
+            result,
+            Err(crate::error::TwygError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_logger_dispatch_rejects_zero_length_limits() {
+        for config in [
+            "max_value_len = 0",
+            "max_message_len = 0",
+            "max_line_len = 0",
+        ] {
+            let opts: Opts = toml::from_str(config).unwrap();
+            let result = Logger::new(opts).dispatch();
+            assert!(matches!(
+                result,
+                Err(crate::error::TwygError::ConfigError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_twyg_logger_rate_limit() {
+        use std::time::Duration;
+
+        use crate::clock::ManualClock;
+        use crate::ratelimit::{RateLimit, RateLimitKey};
+
+        let path =
+            std::env::temp_dir().join(format!("twyg-test-rate-limit-{}.log", std::process::id()));
+        let clock = Arc::new(ManualClock::new(chrono::Utc::now()));
+        let opts = OptsBuilder::new()
+            .level(LogLevel::Warn)
+            .timestamp_format(TSFormat::None)
+            .report_caller(true)
+            .clock(clock.clone())
+            .rate_limit(RateLimit {
+                max_records: 2,
+                interval_ms: 1000,
+                per: RateLimitKey::CallSite,
+            })
+            .output(Output::file(&path))
+            .build()
+            .unwrap();
+        let output = OutputWriter::File(BufWriter::new(File::create(&path).unwrap()));
+        let logger = TwygLogger::new(&opts, output);
+        let log = |line| {
+            logger.log(
+                &log::Record::builder()
+                    .level(Level::Warn)
+                    .target("dep")
+                    .file(Some("retry.rs"))
+                    .line(Some(line))
+                    .key_values(&[("attempt", 1)])
+                    .args(format_args!("retrying"))
+                    .build(),
+            )
+        };
+
+        for _ in 0..1236 {
+            log(9);
+        }
+        log(12);
+        clock.advance(Duration::from_secs(1));
+        log(9);
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "WARN [retry.rs:9 dep] ▶ retrying: attempt={1}\n\
+             WARN [retry.rs:9 dep] ▶ retrying: attempt={1}\n\
+             WARN [retry.rs:12 dep] ▶ retrying: attempt={1}\n\
+             WARN [retry.rs:9 dep] ▶ suppressed 1,234 similar messages\n\
+             WARN [retry.rs:9 dep] ▶ retrying: attempt={1}\n"
+        );
+
+        // A key that never logs again is summarised on flush
+        log(9);
+        log(9);
+        log(9);
+        logger.flush();
+        logger.flush();
+        assert!(std::fs::read_to_string(&path).unwrap().ends_with(
+            "WARN [retry.rs:9 dep] ▶ retrying: attempt={1}\n\
+             WARN [retry.rs:9 dep] ▶ suppressed 2 similar messages\n"
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_twyg_logger_redacts_message_patterns() {
+        use crate::redact::{MaskStyle, Redaction};
+
+        let card = "4111111111114242";
+        let contents = write_to_file(
+            "redact-message",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .redaction(Redaction {
+                    patterns: vec![r"\b\d{13,19}\b".to_string()],
+                    style: MaskStyle::Partial,
+                    ..Redaction::default()
+                }),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("test")
+                .args(format_args!("charged card {}", card))
+                .build(),
+        );
+        assert_eq!(contents, "INFO [test] ▶ charged card ***4242\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_sanitizes_message_and_fields() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .key_values(&[("input", "a\u{7}\nb")])
+            .args(format_args!(
+                "bad\n2026-01-15 14:30:52 ERROR [app] ▶ \u{1b}[31mforged\u{1b}[0m"
+            ))
+            .build();
+
+        let contents = write_to_file(
+            "sanitize-escape",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .sanitize(true),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ bad\\n2026-01-15 14:30:52 ERROR [app] ▶ forged: \
+             input={a\\u{7}\\nb}\n"
+        );
+
+        let contents = write_to_file(
+            "sanitize-indent",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .sanitize(true)
+                .newline_mode(NewlineMode::Indent),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [test] ▶ bad\n    2026-01-15 14:30:52 ERROR [app] ▶ forged: \
+             input={a\\u{7}\n    b}\n"
+        );
+    }
+
+    #[test]
+    fn test_fallback_line_is_sanitized_and_redacted() {
+        let error = io::Error::other("disk full");
+        let record = log::Record::builder()
+            .level(Level::Warn)
+            .target("test")
+            .args(format_args!("card 4111111111114242\n\u{1b}[31mforged"))
+            .build();
+
+        let opts = OptsBuilder::new().sanitize(true).build().unwrap();
+        assert_eq!(
+            fallback_line(&LoggerConfig::new(&opts), &record, &error),
+            "[twyg error: disk full] WARN - card 4111111111114242\\nforged\n"
+        );
+
+        #[cfg(feature = "regex")]
+        {
+            let opts = OptsBuilder::new()
+                .sanitize(true)
+                .redaction(crate::redact::Redaction {
+                    patterns: vec![r"\b\d{13,19}\b".to_string()],
+                    ..Default::default()
+                })
+                .build()
+                .unwrap();
+            assert_eq!(
+                fallback_line(&LoggerConfig::new(&opts), &record, &error),
+                "[twyg error: disk full] WARN - card ***\\nforged\n"
+            );
+        }
+    }
+
+    #[test]
+    fn test_twyg_logger_sanitizes_scoped_and_static_fields_and_target() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("app\u{1b}[2J\nINFO [forged]")
+            .args(format_args!("request"))
+            .build();
+        let mut fields = BTreeMap::new();
+        fields.insert("region\u{1b}[31m".to_string(), "eu\nwest".to_string());
+        let opts = || {
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .fields(fields.clone())
+                .sanitize(true)
+        };
+
+        let _guard = crate::context::push_field("user\nid", "alice\u{1b}]0;pwned\u{7}\nbob");
+        let contents = write_to_file("sanitize-scoped", opts(), &record);
+        assert_eq!(
+            contents,
+            "INFO [app\\nINFO [forged]] ▶ request: user\\nid={alice\\nbob}, \
+             region={eu\\nwest}\n"
+        );
+
+        // Keys and the target stay on one line even when newlines are kept
+        let contents = write_to_file(
+            "sanitize-scoped-indent",
+            opts()
+                .newline_mode(NewlineMode::Indent)
+                .field_layout(FieldLayout::Pretty),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [app\\nINFO [forged]] ▶ request\n\
+             \x20   user\\nid = alice\n\
+             \x20              bob\n\
+             \x20   region   = eu\n\
+             \x20              west\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_indents_multiline_messages() {
+        let record = log::Record::builder()
+            .level(Level::Error)
+            .target("app")
+            .key_values(&[("code", 42)])
+            .args(format_args!(
+                "Query failed:\nrelation \"users\"\ndoes not exist"
+            ))
+            .build();
+
+        let contents = write_to_file(
+            "multiline-raw",
+            OptsBuilder::new().timestamp_format(TSFormat::None),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "ERROR [app] ▶ Query failed:\nrelation \"users\"\ndoes not exist: code={42}\n"
+        );
+
+        let contents = write_to_file(
+            "multiline-indent",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .indent_multiline(true),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "ERROR [app] ▶ Query failed:\n\
+             \x20             relation \"users\"\n\
+             \x20             does not exist: code={42}\n"
+        );
+
+        let contents = write_to_file(
+            "multiline-gutter",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .indent_multiline(true)
+                .gutter_char("│"),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "ERROR [app] ▶ Query failed:\n\
+             \x20           │ relation \"users\"\n\
+             \x20           │ does not exist: code={42}\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_indent_multiline_ignores_colour_codes() {
+        let opts = OptsBuilder::new()
+            .coloured(true)
+            .file_coloured(true)
+            .timestamp_format(TSFormat::None)
+            .indent_multiline(true)
+            .gutter_char("|");
+        let contents = write_to_file(
+            "multiline-coloured",
+            opts,
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .args(format_args!("first\nsecond"))
+                .build(),
+        );
+        let (_, second) = contents.split_once('\n').unwrap();
+        let second = crate::sanitize::Sanitized::new(second, NewlineMode::Indent).to_string();
+        assert_eq!(second, "           | second\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_indent_multiline_with_sanitize() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("app")
+            .args(format_args!("a\nb\u{1b}[2J"))
+            .build();
+        let opts = || {
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .sanitize(true)
+                .indent_multiline(true)
+        };
+
+        // Escaped newlines leave nothing to indent
+        let contents = write_to_file("multiline-escaped", opts(), &record);
+        assert_eq!(contents, "INFO [app] ▶ a\\nb\n");
+
+        let contents = write_to_file(
+            "multiline-sanitized",
+            opts().newline_mode(NewlineMode::Indent),
+            &record,
+        );
+        assert_eq!(contents, "INFO [app] ▶ a\n             b\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_pretty_fields() {
+        use crate::kv::FieldLayout;
+
+        let contents = write_to_file(
+            "pretty-fields",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field_layout(FieldLayout::Pretty)
+                .field("region", "us-east-1")
+                .redaction(crate::redact::Redaction {
+                    keys: vec!["token".to_string()],
+                    ..Default::default()
+                }),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .key_values(&[("method", "GET"), ("status", "200"), ("token", "abc")])
+                .args(format_args!("Request handled"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "INFO [app] ▶ Request handled\n\
+             \x20   method = GET\n\
+             \x20   status = 200\n\
+             \x20   token  = ***\n\
+             \x20   region = us-east-1\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_pretty_fields_after_indented_message() {
+        use crate::kv::FieldLayout;
+
+        let contents = write_to_file(
+            "pretty-multiline",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field_layout(FieldLayout::Pretty)
+                .indent_multiline(true),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .key_values(&[("id", 7)])
+                .args(format_args!("first\nsecond"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "INFO [app] ▶ first\n             second\n    id = 7\n"
+        );
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_twyg_logger_pretty_nested_fields() {
+        use crate::kv::FieldLayout;
+
+        let config = serde_json::json!({
+            "port": 8080,
+            "hosts": ["a", "b"],
+            "db": {"user": "app", "password": "hunter2", "pool": []},
+        });
+        let contents = write_to_file(
+            "pretty-nested",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field_layout(FieldLayout::Pretty)
+                .redaction(crate::redact::Redaction {
+                    keys: vec!["password".to_string()],
+                    ..Default::default()
+                }),
+            &log::Record::builder()
+                .level(Level::Debug)
+                .target("app")
+                .key_values(&[
+                    ("config", Value::from_serde(&config)),
+                    ("id", Value::from(7)),
+                ])
+                .args(format_args!("Loaded"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "DEBUG [app] ▶ Loaded\n\
+             \x20   config =\n\
+             \x20     db:\n\
+             \x20       password: ***\n\
+             \x20       pool: []\n\
+             \x20       user: app\n\
+             \x20     hosts:\n\
+             \x20       - a\n\
+             \x20       - b\n\
+             \x20     port: 8080\n\
+             \x20   id     = 7\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_kv_style() {
+        use crate::kv::{FieldOrder, ValueBrackets};
+
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("app")
+            .key_values(&[("user", "bob \"the builder\""), ("id", "7")])
+            .args(format_args!("done"))
+            .build();
+        let opts = || {
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field("region", "us-east-1")
+        };
+
+        let contents = write_to_file(
+            "kv-quotes",
+            opts()
+                .value_brackets(ValueBrackets::Quotes)
+                .pair_separator(" ")
+                .msg_separator(" "),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [app] ▶ done user=\"bob \\\"the builder\\\"\" id=\"7\" region=\"us-east-1\"\n"
+        );
+
+        let contents = write_to_file(
+            "kv-plain-sorted",
+            opts()
+                .value_brackets(ValueBrackets::None)
+                .kv_separator(": ")
+                .pair_separator("; ")
+                .field_order(FieldOrder::Alphabetical),
+            &record,
+        );
+        assert_eq!(
+            contents,
+            "INFO [app] ▶ done: id: 7; region: us-east-1; user: bob \"the builder\"\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_max_value_len() {
+        let contents = write_to_file(
+            "kv-max-len",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .max_value_len(5),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .key_values(&[("body", "0123456789"), ("ok", "short")])
+                .args(format_args!("sent"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "INFO [app] ▶ sent: body={01234…[+5 bytes]}, ok={short}\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_pretty_fields_sorted() {
+        use crate::kv::{FieldLayout, FieldOrder};
+
+        let contents = write_to_file(
+            "pretty-sorted",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field_layout(FieldLayout::Pretty)
+                .field_order(FieldOrder::Alphabetical),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .key_values(&[("zone", "b"), ("account", "a")])
+                .args(format_args!("moved"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "INFO [app] ▶ moved\n    account = a\n    zone    = b\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_max_message_len() {
+        let huge = vec![0u8; 1000];
+        let contents = write_to_file(
+            "max-message-len",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .max_message_len(12),
+            &log::Record::builder()
+                .level(Level::Debug)
+                .target("app")
+                .key_values(&[("len", 1000)])
+                .args(format_args!("{:?}", huge))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "DEBUG [app] ▶ [0, 0, 0, 0,…[+2988 bytes]: len={1000}\n"
+        );
+    }
+
+    #[test]
+    fn test_twyg_logger_max_line_len() {
+        let long = "x".repeat(500);
+        let contents = write_to_file(
+            "max-line-len",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .max_line_len(40),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .key_values(&[("body", long.as_str())])
+                .args(format_args!("sent"))
+                .build(),
+        );
+        assert_eq!(contents, "INFO [app] ▶ sent: body…[+503 bytes]\n");
+        assert!(contents.trim_end().len() <= 40);
+    }
+
+    #[test]
+    fn test_twyg_logger_max_line_len_per_line() {
+        use crate::kv::FieldLayout;
+
+        let long = "x".repeat(500);
+        let contents = write_to_file(
+            "max-line-len-pretty",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .field_layout(FieldLayout::Pretty)
+                .indent_multiline(true)
+                .max_line_len(60),
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .key_values(&[("body", long.as_str()), ("id", "7")])
+                .args(format_args!("first\nsecond"))
+                .build(),
+        );
+        assert_eq!(
+            contents,
+            "INFO [app] ▶ first\n\
+             \x20            second\n\
+             \x20   body = xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx…[+466 bytes]\n\
+             \x20   id   = 7\n"
+        );
+        assert!(contents.lines().all(|line| line.len() <= 60), "{contents}");
+    }
+
+    fn write_on_thread(name: &'static str, opts: OptsBuilder) -> String {
+        std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                let record = log::Record::builder()
+                    .level(Level::Info)
+                    .target("test")
+                    .file(Some("pool.rs"))
+                    .line(Some(3))
+                    .args(format_args!("job done"))
+                    .build();
+                write_to_file(name, opts, &record)
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_twyg_logger_thread() {
+        let contents = write_on_thread(
+            "thread-plain",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .report_thread(true)
+                .report_caller(true),
+        );
+        assert_eq!(
+            contents,
+            "INFO [thread-plain] [pool.rs:3 test] ▶ job done\n"
+        );
+
+        let contents = write_on_thread(
+            "thread-padded",
+            OptsBuilder::new()
+                .timestamp_format(TSFormat::None)
+                .report_thread(true)
+                .pad_thread(true)
+                .thread_pad_amount(16)
+                .thread_pad_side(PadSide::Left),
+        );
+        assert_eq!(contents, "INFO [   thread-padded] [test] ▶ job done\n");
+    }
+
+    #[test]
+    fn test_twyg_logger_thread_color() {
+        owo_colors::set_override(true);
+        let contents = write_on_thread(
+            "thread-colour",
+            OptsBuilder::new()
+                .coloured(true)
+                .file_coloured(true)
+                .report_thread(true)
+                .pad_thread(true)
+                .thread_pad_amount(14),
+        );
+        assert!(contents.contains("[\x1b[35mthread-colour \x1b[39m]"));
+    }
+
+    #[test]
+    fn test_twyg_logger_caller_column() {
+        let kvs = [(COLUMN_KEY, log::kv::Value::from(9u32))];
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .file(Some("main.rs"))
+            .line(Some(12))
+            .key_values(&kvs)
+            .args(format_args!("with column"))
+            .build();
+
+        let with_column = write_to_file(
+            "column",
+            OptsBuilder::new().report_caller(true).report_column(true),
+            &record,
+        );
+        assert!(with_column.contains("[main.rs:12:9 test] ▶ with column\n"));
+
+        let without_column =
+            write_to_file("no-column", OptsBuilder::new().report_caller(true), &record);
+        assert!(without_column.contains("[main.rs:12 test] ▶ with column\n"));
+    }
+
+    #[test]
+    fn test_twyg_logger_caller_file_and_line_coloured_separately() {
+        let colors = Colors {
+            caller_file: Some(Color::red()),
+            caller_line: Some(Color::blue()),
+            ..Colors::default()
+        };
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .file(Some("main.rs"))
+            .line(Some(12))
+            .args(format_args!("coloured caller"))
+            .build();
+        owo_colors::set_override(true);
+
+        let contents = write_to_file(
+            "caller-colours",
+            OptsBuilder::new()
+                .coloured(true)
+                .file_coloured(true)
+                .report_caller(true)
+                .colors(colors),
+            &record,
+        );
+        assert!(contents.contains("\x1b[31mmain.rs\x1b[39m:\x1b[34m12\x1b[39m"));
+    }
+
+    #[test]
+    fn test_twyg_logger_kv_colours_by_field_and_type() {
+        let colors = Colors {
+            attr_number: Some(Color::magenta()),
+            attr_bool: Some(Color::blue()),
+            attr_overrides: BTreeMap::from([("error".to_string(), Color::red())]),
+            ..Colors::default()
+        };
+        let kvs: [(&str, log::kv::Value); 3] = [
+            ("duration_ms", 12.into()),
+            ("cached", true.into()),
+            ("error", "timeout".into()),
+        ];
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("test")
+            .key_values(&kvs)
+            .args(format_args!("request"))
+            .build();
+        owo_colors::set_override(true);
+
+        let contents = write_to_file(
+            "kv-colours",
+            OptsBuilder::new()
+                .coloured(true)
+                .file_coloured(true)
+                .colors(colors),
+            &record,
+        );
+        assert!(contents.contains("duration_ms\x1b[39m=\x1b[35m{12}"));
+        assert!(contents.contains("cached\x1b[39m=\x1b[34m{true}"));
+        assert!(contents.contains("\x1b[31merror\x1b[39m=\x1b[31m{timeout}"));
+    }
+
+    #[test]
+    fn test_twyg_logger_target_override_colour() {
+        let colors = Colors {
+            target_overrides: BTreeMap::from([("app::db".to_string(), Color::magenta())]),
+            ..Colors::default()
+        };
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("app::db::pool")
+            .args(format_args!("connected"))
+            .build();
+        owo_colors::set_override(true);
+
+        let contents = write_to_file(
+            "target-override",
+            OptsBuilder::new()
+                .coloured(true)
+                .file_coloured(true)
+                .target_format(TargetFormat::Abbreviated)
+                .colors(colors),
+            &record,
+        );
+        // Overrides match the full target, not the abbreviated display
+        assert!(contents.contains("[\x1b[35ma::d::pool\x1b[39m]"));
+    }
+
+    #[test]
+    fn test_twyg_logger_caller_path() {
+        let record = log::Record::builder()
+            .level(Level::Info)
+            .target("app")
+            .module_path(Some("app::server::handler"))
+            .file(Some("/build/app/src/server/handler.rs"))
+            .line(Some(3))
+            .args(format_args!("handled"))
+            .build();
+
+        let basename = write_to_file(
+            "caller-basename",
+            OptsBuilder::new()
+                .report_caller(true)
+                .caller_path(CallerPath::Basename),
+            &record,
+        );
+        assert!(basename.contains("[handler.rs:3 app]"));
+
+        let module_path = write_to_file(
+            "caller-module-path",
+            OptsBuilder::new()
+                .report_caller(true)
+                .caller_path(CallerPath::ModulePath),
+            &record,
+        );
+        assert!(module_path.contains("[app::server::handler:3 app]"));
+    }
 }
@@ -0,0 +1,126 @@
+//! Logging macros that capture the caller's column.
+//!
+//! The `log` crate's [`Record`](log::Record) only carries the file and line of
+//! a logging call. These macros wrap the standard `log` macros and attach the
+//! call site's `column!()` as a reserved key-value pair, which twyg strips from
+//! the structured fields and renders as `file:line:column` when
+//! [`OptsBuilder::report_column`](crate::OptsBuilder::report_column) is enabled.
+//!
+//! They accept the same arguments as their `log` counterparts:
+//!
+//! ```
+//! twyg::info!("Application started");
+//! twyg::warn!(target: "app::config", "Using default configuration");
+//! twyg::debug!(user = "alice", id = 42; "User logged in");
+//! ```
+
+/// Reserved key under which the twyg macros record the caller's column.
+///
+/// Must match the literal used in [`log!`](crate::log!).
+pub(crate) const COLUMN_KEY: &str = "twyg.column";
+
+/// Logs a message at the given level, recording the caller's column.
+///
+/// This is a drop-in replacement for [`log::log!`].
+///
+/// # Examples
+///
+/// ```
+/// use log::Level;
+///
+/// twyg::log!(Level::Info, "a {} event", "log");
+/// twyg::log!(target: "my_target", Level::Warn, key = 42; "an event");
+/// ```
+#[macro_export]
+macro_rules! log {
+    // log!(target: "my_target", Level::Info, key1 = 42, key2 = true; "a {} event", "log");
+    (target: $target:expr, $lvl:expr, $($key:tt $(:$capture:tt)? $(= $value:expr)?),+; $($arg:tt)+) => {
+        $crate::__private::log::log!(
+            target: $target,
+            $lvl,
+            "twyg.column" = ::core::column!(),
+            $($key $(:$capture)? $(= $value)?),+;
+            $($arg)+
+        )
+    };
+
+    // log!(target: "my_target", Level::Info, "a {} event", "log");
+    (target: $target:expr, $lvl:expr, $($arg:tt)+) => {
+        $crate::__private::log::log!(
+            target: $target,
+            $lvl,
+            "twyg.column" = ::core::column!();
+            $($arg)+
+        )
+    };
+
+    // log!(Level::Info, "a {} event", "log");
+    ($lvl:expr, $($arg:tt)+) => {
+        $crate::log!(target: ::core::module_path!(), $lvl, $($arg)+)
+    };
+}
+
+/// Logs a message at the error level, recording the caller's column.
+///
+/// This is a drop-in replacement for [`log::error!`].
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::__private::log::Level::Error, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::__private::log::Level::Error, $($arg)+)
+    };
+}
+
+/// Logs a message at the warn level, recording the caller's column.
+///
+/// This is a drop-in replacement for [`log::warn!`].
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::__private::log::Level::Warn, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::__private::log::Level::Warn, $($arg)+)
+    };
+}
+
+/// Logs a message at the info level, recording the caller's column.
+///
+/// This is a drop-in replacement for [`log::info!`].
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::__private::log::Level::Info, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::__private::log::Level::Info, $($arg)+)
+    };
+}
+
+/// Logs a message at the debug level, recording the caller's column.
+///
+/// This is a drop-in replacement for [`log::debug!`].
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::__private::log::Level::Debug, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::__private::log::Level::Debug, $($arg)+)
+    };
+}
+
+/// Logs a message at the trace level, recording the caller's column.
+///
+/// This is a drop-in replacement for [`log::trace!`].
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::__private::log::Level::Trace, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::__private::log::Level::Trace, $($arg)+)
+    };
+}
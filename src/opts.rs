@@ -2,14 +2,26 @@
 //!
 //! This module provides the [`Opts`] struct for configuring the twyg logger.
 
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use chrono::Local;
 use serde::{Deserialize, Serialize};
 
+use super::caller::CallerPath;
+use super::clock::{Clock, SystemClock};
 use super::color::Colors;
 use super::error::{Result, TwygError};
-use super::level::LogLevel;
+use super::kv::{FieldLayout, FieldOrder, ValueBrackets};
+use super::level::{LevelLabels, LogLevel};
 use super::output::Output;
-use super::timestamp::TSFormat;
+use super::process::ProcessInfo;
+use super::ratelimit::RateLimit;
+use super::redact::Redaction;
+use super::sanitize::NewlineMode;
+use super::target::TargetFormat;
+use super::thread::ThreadFormat;
+use super::timestamp::{TSFormat, Timezone};
 
 const DEFAULT_TS_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
@@ -24,6 +36,17 @@ pub enum PadSide {
     Right,
 }
 
+/// Side to truncate strings that exceed a fixed width.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TruncateSide {
+    /// Drop characters from the start (keep the end)
+    #[default]
+    Left,
+
+    /// Drop characters from the end (keep the start)
+    Right,
+}
+
 /// Logger configuration options.
 ///
 /// Configure all aspects of the twyg logger including output destination,
@@ -48,6 +71,12 @@ pub struct Opts {
     #[serde(default)]
     coloured: bool,
 
+    /// Allow ANSI escape codes in file output (e.g. for viewing with `less -R`).
+    ///
+    /// File outputs are written plain unless both this and `coloured` are set.
+    #[serde(default)]
+    file_coloured: bool,
+
     /// Output destination (stdout, stderr, or file).
     #[serde(default)]
     output: Output,
@@ -60,22 +89,166 @@ pub struct Opts {
     #[serde(default)]
     report_caller: bool,
 
+    /// Include the column after the line number in caller output.
+    ///
+    /// Only records logged through twyg's macros (e.g. `twyg::info!`) carry
+    /// a column; records from the `log` macros show file and line only.
+    #[serde(default)]
+    report_column: bool,
+
+    /// How the caller's file is displayed.
+    #[serde(default)]
+    caller_path: CallerPath,
+
     /// Timestamp format (enum with presets + custom).
     #[serde(default)]
     timestamp_format: TSFormat,
 
+    /// Timezone timestamps are rendered in.
+    #[serde(default)]
+    timezone: Timezone,
+
+    /// Time source for timestamps; not part of serialized configuration.
+    #[serde(skip, default = "default_clock")]
+    clock: Arc<dyn Clock>,
+
     /// Enable level padding for alignment.
     #[serde(default)]
     pad_level: bool,
 
-    /// Number of characters to pad level to.
-    #[serde(default = "default_pad_amount")]
-    pad_amount: usize,
+    /// Number of columns to pad level to; wider level labels widen it
+    /// (default: the width of the widest level label).
+    #[serde(default)]
+    pad_amount: Option<usize>,
+
+    /// The label shown for each level.
+    #[serde(default)]
+    level_labels: LevelLabels,
 
     /// Which side to pad the level string.
     #[serde(default)]
     pad_side: PadSide,
 
+    /// How the target is displayed.
+    #[serde(default)]
+    target_format: TargetFormat,
+
+    /// Render the target at a fixed width, padding or truncating it.
+    #[serde(default)]
+    pad_target: bool,
+
+    /// Number of characters to fit the target to.
+    #[serde(default = "default_target_pad_amount")]
+    target_pad_amount: usize,
+
+    /// Which side to pad the target string.
+    #[serde(default)]
+    target_pad_side: PadSide,
+
+    /// Which side to truncate a target that is too long.
+    #[serde(default)]
+    target_truncate_side: TruncateSide,
+
+    /// Include the logging thread's name or ID in log output.
+    #[serde(default)]
+    report_thread: bool,
+
+    /// How the logging thread is displayed.
+    #[serde(default)]
+    thread_format: ThreadFormat,
+
+    /// Enable thread padding for alignment.
+    #[serde(default)]
+    pad_thread: bool,
+
+    /// Number of characters to pad the thread to.
+    #[serde(default = "default_thread_pad_amount")]
+    thread_pad_amount: usize,
+
+    /// Which side to pad the thread string.
+    #[serde(default)]
+    thread_pad_side: PadSide,
+
+    /// Show the process metadata as fields on every line.
+    #[serde(default)]
+    report_process: bool,
+
+    /// Process metadata: service, version, environment, hostname and pid.
+    #[serde(default)]
+    process: ProcessInfo,
+
+    /// Static fields added to every record, e.g. `region = "us-east-1"`.
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+
+    /// Masking of sensitive field values and message text.
+    #[serde(default)]
+    redaction: Redaction,
+
+    /// Escape control characters and strip ANSI escape sequences in messages,
+    /// field keys and values, targets, thread names and caller files, so
+    /// logged input can't forge lines.
+    #[serde(default)]
+    sanitize: bool,
+
+    /// How newlines are written when sanitising (default: Escape).
+    #[serde(default)]
+    newline_mode: NewlineMode,
+
+    /// Indent the continuation lines of multi-line messages under the
+    /// message column.
+    #[serde(default)]
+    indent_multiline: bool,
+
+    /// Gutter drawn under the arrow on continuation lines, e.g. "│",
+    /// styled like the arrow (default: none).
+    #[serde(default)]
+    gutter_char: Option<String>,
+
+    /// Where key-value pairs are written: inline after the message or
+    /// beneath it, one per line (default: Inline).
+    #[serde(default)]
+    field_layout: FieldLayout,
+
+    /// What surrounds inline values: braces, quotes or nothing (default:
+    /// Braces).
+    #[serde(default)]
+    value_brackets: ValueBrackets,
+
+    /// Separator between a key and its value (default: "=").
+    #[serde(default = "default_kv_separator")]
+    kv_separator: String,
+
+    /// Separator between inline key-value pairs (default: ", ").
+    #[serde(default = "default_pair_separator")]
+    pair_separator: String,
+
+    /// Order of key-value pairs: as logged or sorted by key (default:
+    /// Insertion).
+    #[serde(default)]
+    field_order: FieldOrder,
+
+    /// Longest value shown, in terminal columns; longer values are cut
+    /// short with a marker giving the number of bytes dropped (default: no
+    /// limit).
+    #[serde(default)]
+    max_value_len: Option<usize>,
+
+    /// Longest message written, in bytes; the rest is replaced with a
+    /// marker giving the number of bytes dropped (default: no limit).
+    #[serde(default)]
+    max_message_len: Option<usize>,
+
+    /// Longest line written, in bytes including colour codes, marker
+    /// included; each line of a multi-line record is limited on its own
+    /// (default: no limit).
+    #[serde(default)]
+    max_line_len: Option<usize>,
+
+    /// Cap on records written per call site or target (default: none).
+    #[serde(default)]
+    rate_limit: Option<RateLimit>,
+
     /// Separator between message and attributes (default: ": ").
     #[serde(default = "default_msg_separator")]
     msg_separator: String,
@@ -90,8 +263,24 @@ pub struct Opts {
 }
 
 // Default value functions for serde
-fn default_pad_amount() -> usize {
-    5
+fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+fn default_target_pad_amount() -> usize {
+    20
+}
+
+fn default_thread_pad_amount() -> usize {
+    12
+}
+
+fn default_kv_separator() -> String {
+    "=".to_string()
+}
+
+fn default_pair_separator() -> String {
+    ", ".to_string()
 }
 
 fn default_msg_separator() -> String {
@@ -106,13 +295,46 @@ impl Default for Opts {
     fn default() -> Self {
         Self {
             coloured: false,
+            file_coloured: false,
             output: Output::default(),
             level: LogLevel::default(),
             report_caller: false,
+            report_column: false,
+            caller_path: CallerPath::default(),
             timestamp_format: TSFormat::default(),
+            timezone: Timezone::default(),
+            clock: default_clock(),
             pad_level: false,
-            pad_amount: 5,
+            pad_amount: None,
+            level_labels: LevelLabels::default(),
             pad_side: PadSide::default(),
+            target_format: TargetFormat::default(),
+            pad_target: false,
+            target_pad_amount: 20,
+            target_pad_side: PadSide::default(),
+            target_truncate_side: TruncateSide::default(),
+            report_thread: false,
+            thread_format: ThreadFormat::default(),
+            pad_thread: false,
+            thread_pad_amount: 12,
+            thread_pad_side: PadSide::default(),
+            report_process: false,
+            process: ProcessInfo::default(),
+            fields: BTreeMap::new(),
+            redaction: Redaction::default(),
+            sanitize: false,
+            newline_mode: NewlineMode::default(),
+            indent_multiline: false,
+            gutter_char: None,
+            field_layout: FieldLayout::default(),
+            value_brackets: ValueBrackets::default(),
+            kv_separator: "=".to_string(),
+            pair_separator: ", ".to_string(),
+            field_order: FieldOrder::default(),
+            max_value_len: None,
+            max_message_len: None,
+            max_line_len: None,
+            rate_limit: None,
             msg_separator: ": ".to_string(),
             arrow_char: "▶".to_string(),
             colors: Colors::default(),
@@ -139,6 +361,11 @@ impl Opts {
         self.coloured
     }
 
+    /// Returns whether colored output is enabled for file destinations.
+    pub fn file_coloured(&self) -> bool {
+        self.file_coloured
+    }
+
     /// Returns the output destination.
     pub fn output(&self) -> &Output {
         &self.output
@@ -154,19 +381,51 @@ impl Opts {
         self.report_caller
     }
 
+    /// Returns whether caller column reporting is enabled.
+    pub fn report_column(&self) -> bool {
+        self.report_column
+    }
+
+    /// Returns how the caller's file is displayed.
+    pub fn caller_path(&self) -> &CallerPath {
+        &self.caller_path
+    }
+
     /// Returns the timestamp format.
     pub fn timestamp_format(&self) -> &TSFormat {
         &self.timestamp_format
     }
 
+    /// Returns the timezone timestamps are rendered in.
+    pub fn timezone(&self) -> &Timezone {
+        &self.timezone
+    }
+
+    /// Returns the clock timestamps are read from.
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
     /// Returns whether level padding is enabled.
     pub fn pad_level(&self) -> bool {
         self.pad_level
     }
 
-    /// Returns the padding amount.
+    /// Returns the padding amount: the amount set, or the width of the
+    /// widest level label if none was.
     pub fn pad_amount(&self) -> usize {
-        self.pad_amount
+        self.pad_amount.unwrap_or_else(|| self.level_labels.width())
+    }
+
+    /// Returns the number of columns padded levels take: the padding
+    /// amount, or the width of the widest level label if that is wider.
+    pub fn level_width(&self) -> usize {
+        self.pad_amount().max(self.level_labels.width())
+    }
+
+    /// Returns the level labels.
+    pub fn level_labels(&self) -> &LevelLabels {
+        &self.level_labels
     }
 
     /// Returns the padding side.
@@ -174,6 +433,141 @@ impl Opts {
         self.pad_side
     }
 
+    /// Returns the target format.
+    pub fn target_format(&self) -> TargetFormat {
+        self.target_format
+    }
+
+    /// Returns whether fixed-width target rendering is enabled.
+    pub fn pad_target(&self) -> bool {
+        self.pad_target
+    }
+
+    /// Returns the target width.
+    pub fn target_pad_amount(&self) -> usize {
+        self.target_pad_amount
+    }
+
+    /// Returns the target padding side.
+    pub fn target_pad_side(&self) -> PadSide {
+        self.target_pad_side
+    }
+
+    /// Returns the target truncation side.
+    pub fn target_truncate_side(&self) -> TruncateSide {
+        self.target_truncate_side
+    }
+
+    /// Returns whether the thread is reported.
+    pub fn report_thread(&self) -> bool {
+        self.report_thread
+    }
+
+    /// Returns the thread format.
+    pub fn thread_format(&self) -> ThreadFormat {
+        self.thread_format
+    }
+
+    /// Returns whether the thread is padded.
+    pub fn pad_thread(&self) -> bool {
+        self.pad_thread
+    }
+
+    /// Returns the thread padding amount.
+    pub fn thread_pad_amount(&self) -> usize {
+        self.thread_pad_amount
+    }
+
+    /// Returns the thread padding side.
+    pub fn thread_pad_side(&self) -> PadSide {
+        self.thread_pad_side
+    }
+
+    /// Returns whether the process metadata is reported.
+    pub fn report_process(&self) -> bool {
+        self.report_process
+    }
+
+    /// Returns the process metadata.
+    pub fn process(&self) -> &ProcessInfo {
+        &self.process
+    }
+
+    /// Returns the static fields added to every record.
+    pub fn fields(&self) -> &BTreeMap<String, String> {
+        &self.fields
+    }
+
+    /// Returns the redaction configuration.
+    pub fn redaction(&self) -> &Redaction {
+        &self.redaction
+    }
+
+    /// Returns whether messages, fields, targets and thread names are sanitised.
+    pub fn sanitize(&self) -> bool {
+        self.sanitize
+    }
+
+    /// Returns how newlines are written when sanitising.
+    pub fn newline_mode(&self) -> NewlineMode {
+        self.newline_mode
+    }
+
+    /// Returns whether continuation lines are indented under the message.
+    pub fn indent_multiline(&self) -> bool {
+        self.indent_multiline
+    }
+
+    /// Returns the gutter drawn on continuation lines, if any.
+    pub fn gutter_char(&self) -> Option<&str> {
+        self.gutter_char.as_deref()
+    }
+
+    /// Returns where key-value pairs are written.
+    pub fn field_layout(&self) -> FieldLayout {
+        self.field_layout
+    }
+
+    /// Returns what surrounds inline values.
+    pub fn value_brackets(&self) -> ValueBrackets {
+        self.value_brackets
+    }
+
+    /// Returns the separator between a key and its value.
+    pub fn kv_separator(&self) -> &str {
+        &self.kv_separator
+    }
+
+    /// Returns the separator between inline key-value pairs.
+    pub fn pair_separator(&self) -> &str {
+        &self.pair_separator
+    }
+
+    /// Returns the order key-value pairs are written in.
+    pub fn field_order(&self) -> FieldOrder {
+        self.field_order
+    }
+
+    /// Returns the longest value shown, if limited.
+    pub fn max_value_len(&self) -> Option<usize> {
+        self.max_value_len
+    }
+
+    /// Returns the longest message written, if limited.
+    pub fn max_message_len(&self) -> Option<usize> {
+        self.max_message_len
+    }
+
+    /// Returns the longest line written, if limited.
+    pub fn max_line_len(&self) -> Option<usize> {
+        self.max_line_len
+    }
+
+    /// Returns the rate limit, if any.
+    pub fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+
     /// Returns the message separator.
     pub fn msg_separator(&self) -> &str {
         &self.msg_separator
@@ -194,6 +588,7 @@ impl Opts {
     pub fn time_format(&self) -> Option<&str> {
         match &self.timestamp_format {
             TSFormat::Custom(s) => Some(s.as_str()),
+            TSFormat::Elapsed | TSFormat::Delta | TSFormat::None => None,
             _ => Some(self.timestamp_format.to_format_string()),
         }
     }
@@ -216,13 +611,46 @@ impl Opts {
 #[derive(Clone, Debug)]
 pub struct OptsBuilder {
     coloured: bool,
+    file_coloured: bool,
     output: Output,
     level: LogLevel,
     report_caller: bool,
+    report_column: bool,
+    caller_path: CallerPath,
     timestamp_format: TSFormat,
+    timezone: Timezone,
+    clock: Arc<dyn Clock>,
     pad_level: bool,
-    pad_amount: usize,
+    pad_amount: Option<usize>,
+    level_labels: LevelLabels,
     pad_side: PadSide,
+    target_format: TargetFormat,
+    pad_target: bool,
+    target_pad_amount: usize,
+    target_pad_side: PadSide,
+    target_truncate_side: TruncateSide,
+    report_thread: bool,
+    thread_format: ThreadFormat,
+    pad_thread: bool,
+    thread_pad_amount: usize,
+    thread_pad_side: PadSide,
+    report_process: bool,
+    process: ProcessInfo,
+    fields: BTreeMap<String, String>,
+    redaction: Redaction,
+    sanitize: bool,
+    newline_mode: NewlineMode,
+    indent_multiline: bool,
+    gutter_char: Option<String>,
+    field_layout: FieldLayout,
+    value_brackets: ValueBrackets,
+    kv_separator: String,
+    pair_separator: String,
+    field_order: FieldOrder,
+    max_value_len: Option<usize>,
+    max_message_len: Option<usize>,
+    max_line_len: Option<usize>,
+    rate_limit: Option<RateLimit>,
     msg_separator: String,
     arrow_char: String,
     colors: Colors,
@@ -239,13 +667,46 @@ impl OptsBuilder {
     pub fn new() -> Self {
         Self {
             coloured: false,
+            file_coloured: false,
             output: Output::default(),
             level: LogLevel::default(),
             report_caller: false,
+            report_column: false,
+            caller_path: CallerPath::default(),
             timestamp_format: TSFormat::default(),
+            timezone: Timezone::default(),
+            clock: default_clock(),
             pad_level: false,
-            pad_amount: 5,
+            pad_amount: None,
+            level_labels: LevelLabels::default(),
             pad_side: PadSide::default(),
+            target_format: TargetFormat::default(),
+            pad_target: false,
+            target_pad_amount: 20,
+            target_pad_side: PadSide::default(),
+            target_truncate_side: TruncateSide::default(),
+            report_thread: false,
+            thread_format: ThreadFormat::default(),
+            pad_thread: false,
+            thread_pad_amount: 12,
+            thread_pad_side: PadSide::default(),
+            report_process: false,
+            process: ProcessInfo::default(),
+            fields: BTreeMap::new(),
+            redaction: Redaction::default(),
+            sanitize: false,
+            newline_mode: NewlineMode::default(),
+            indent_multiline: false,
+            gutter_char: None,
+            field_layout: FieldLayout::default(),
+            value_brackets: ValueBrackets::default(),
+            kv_separator: "=".to_string(),
+            pair_separator: ", ".to_string(),
+            field_order: FieldOrder::default(),
+            max_value_len: None,
+            max_message_len: None,
+            max_line_len: None,
+            rate_limit: None,
             msg_separator: ": ".to_string(),
             arrow_char: "▶".to_string(),
             colors: Colors::default(),
@@ -271,6 +732,14 @@ impl OptsBuilder {
         self
     }
 
+    /// Enable or disable colored output for file destinations.
+    ///
+    /// Has no effect unless `coloured` is also enabled.
+    pub fn file_coloured(mut self, coloured: bool) -> Self {
+        self.file_coloured = coloured;
+        self
+    }
+
     /// Set the output destination.
     pub fn output(mut self, output: Output) -> Self {
         self.output = output;
@@ -289,21 +758,70 @@ impl OptsBuilder {
         self
     }
 
+    /// Enable or disable caller column reporting.
+    ///
+    /// Has no effect unless `report_caller` is also enabled. The column is
+    /// only known for records logged through twyg's macros.
+    pub fn report_column(mut self, report: bool) -> Self {
+        self.report_column = report;
+        self
+    }
+
+    /// Set how the caller's file is displayed.
+    pub fn caller_path(mut self, path: CallerPath) -> Self {
+        self.caller_path = path;
+        self
+    }
+
     /// Set the timestamp format.
     pub fn timestamp_format(mut self, format: TSFormat) -> Self {
         self.timestamp_format = format;
         self
     }
 
+    /// Set the timezone timestamps are rendered in.
+    pub fn timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Set the clock timestamps are read from, e.g. a
+    /// [`ManualClock`](crate::ManualClock) for deterministic test output.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Enable or disable level padding.
     pub fn pad_level(mut self, pad: bool) -> Self {
         self.pad_level = pad;
         self
     }
 
-    /// Set the padding amount.
+    /// Set the padding amount; by default it's the width of the widest
+    /// level label.
     pub fn pad_amount(mut self, amount: usize) -> Self {
-        self.pad_amount = amount;
+        self.pad_amount = Some(amount);
+        self
+    }
+
+    /// Set the label shown for each level. Padded levels take the width of
+    /// the widest label, unless `pad_amount` sets a wider one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twyg::{LevelLabels, OptsBuilder};
+    ///
+    /// let opts = OptsBuilder::new()
+    ///     .pad_level(true)
+    ///     .level_labels(LevelLabels::Short)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(opts.level_width(), 3);
+    /// ```
+    pub fn level_labels(mut self, labels: LevelLabels) -> Self {
+        self.level_labels = labels;
         self
     }
 
@@ -313,6 +831,266 @@ impl OptsBuilder {
         self
     }
 
+    /// Set the target format.
+    pub fn target_format(mut self, format: TargetFormat) -> Self {
+        self.target_format = format;
+        self
+    }
+
+    /// Enable or disable fixed-width target rendering.
+    pub fn pad_target(mut self, pad: bool) -> Self {
+        self.pad_target = pad;
+        self
+    }
+
+    /// Set the target width.
+    pub fn target_pad_amount(mut self, amount: usize) -> Self {
+        self.target_pad_amount = amount;
+        self
+    }
+
+    /// Set the target padding side.
+    pub fn target_pad_side(mut self, side: PadSide) -> Self {
+        self.target_pad_side = side;
+        self
+    }
+
+    /// Set the target truncation side.
+    pub fn target_truncate_side(mut self, side: TruncateSide) -> Self {
+        self.target_truncate_side = side;
+        self
+    }
+
+    /// Enable or disable the thread name or ID in output.
+    pub fn report_thread(mut self, report: bool) -> Self {
+        self.report_thread = report;
+        self
+    }
+
+    /// Set the thread format.
+    pub fn thread_format(mut self, format: ThreadFormat) -> Self {
+        self.thread_format = format;
+        self
+    }
+
+    /// Enable or disable thread padding.
+    pub fn pad_thread(mut self, pad: bool) -> Self {
+        self.pad_thread = pad;
+        self
+    }
+
+    /// Set the thread padding amount.
+    pub fn thread_pad_amount(mut self, amount: usize) -> Self {
+        self.thread_pad_amount = amount;
+        self
+    }
+
+    /// Set the thread padding side.
+    pub fn thread_pad_side(mut self, side: PadSide) -> Self {
+        self.thread_pad_side = side;
+        self
+    }
+
+    /// Enable or disable the process metadata fields in output.
+    pub fn report_process(mut self, report: bool) -> Self {
+        self.report_process = report;
+        self
+    }
+
+    /// Set the process metadata.
+    pub fn process(mut self, process: ProcessInfo) -> Self {
+        self.process = process;
+        self
+    }
+
+    /// Add a static field to every record.
+    ///
+    /// A record's own field with the same key takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twyg::OptsBuilder;
+    ///
+    /// let opts = OptsBuilder::new()
+    ///     .field("region", "us-east-1")
+    ///     .field("deployment", "blue")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set all static fields, replacing any added before.
+    pub fn fields(mut self, fields: BTreeMap<String, String>) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Set the redaction configuration.
+    pub fn redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Set whether to escape control characters and strip ANSI escape
+    /// sequences in messages, field keys and values, targets, thread names
+    /// and caller files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twyg::OptsBuilder;
+    ///
+    /// let opts = OptsBuilder::new()
+    ///     .sanitize(true)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    /// Set how newlines are written when sanitising.
+    pub fn newline_mode(mut self, mode: NewlineMode) -> Self {
+        self.newline_mode = mode;
+        self
+    }
+
+    /// Set whether to indent the continuation lines of multi-line messages
+    /// under the message column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twyg::OptsBuilder;
+    ///
+    /// let opts = OptsBuilder::new()
+    ///     .indent_multiline(true)
+    ///     .gutter_char("│")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn indent_multiline(mut self, indent: bool) -> Self {
+        self.indent_multiline = indent;
+        self
+    }
+
+    /// Set the gutter drawn under the arrow on continuation lines.
+    pub fn gutter_char(mut self, gutter: impl Into<String>) -> Self {
+        self.gutter_char = Some(gutter.into());
+        self
+    }
+
+    /// Set where key-value pairs are written.
+    pub fn field_layout(mut self, layout: FieldLayout) -> Self {
+        self.field_layout = layout;
+        self
+    }
+
+    /// Set what surrounds inline values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twyg::{FieldOrder, OptsBuilder, ValueBrackets};
+    ///
+    /// // user="alice" id="7"
+    /// let opts = OptsBuilder::new()
+    ///     .value_brackets(ValueBrackets::Quotes)
+    ///     .pair_separator(" ")
+    ///     .field_order(FieldOrder::Alphabetical)
+    ///     .max_value_len(80)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn value_brackets(mut self, brackets: ValueBrackets) -> Self {
+        self.value_brackets = brackets;
+        self
+    }
+
+    /// Set the separator between a key and its value.
+    pub fn kv_separator(mut self, sep: impl Into<String>) -> Self {
+        self.kv_separator = sep.into();
+        self
+    }
+
+    /// Set the separator between inline key-value pairs.
+    pub fn pair_separator(mut self, sep: impl Into<String>) -> Self {
+        self.pair_separator = sep.into();
+        self
+    }
+
+    /// Set the order key-value pairs are written in.
+    pub fn field_order(mut self, order: FieldOrder) -> Self {
+        self.field_order = order;
+        self
+    }
+
+    /// Set the longest value shown, in terminal columns.
+    pub fn max_value_len(mut self, len: usize) -> Self {
+        self.max_value_len = Some(len);
+        self
+    }
+
+    /// Set the longest message written, in bytes. Longer messages are cut
+    /// at a character boundary and end with a marker giving the number of
+    /// bytes dropped: "…[+52133 bytes]".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twyg::OptsBuilder;
+    ///
+    /// let opts = OptsBuilder::new()
+    ///     .max_message_len(4096)
+    ///     .max_line_len(8192)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn max_message_len(mut self, len: usize) -> Self {
+        self.max_message_len = Some(len);
+        self
+    }
+
+    /// Set the longest line written, in bytes including the marker for
+    /// dropped bytes. Colour codes aren't counted, so coloured and plain
+    /// output are cut at the same place. Each line of a multi-line record
+    /// is limited on its own.
+    pub fn max_line_len(mut self, len: usize) -> Self {
+        self.max_line_len = Some(len);
+        self
+    }
+
+    /// Cap the records written per call site or target in each interval.
+    ///
+    /// Records over the cap are dropped, and once the call site or target
+    /// writes again after its interval, a summary such as "suppressed
+    /// 1,234 similar messages" is written before the record. Counts still
+    /// pending are summarised when the logger is flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twyg::{OptsBuilder, RateLimit, RateLimitKey};
+    ///
+    /// let opts = OptsBuilder::new()
+    ///     .rate_limit(RateLimit {
+    ///         max_records: 10,
+    ///         interval_ms: 60_000,
+    ///         per: RateLimitKey::Target,
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
     /// Set the message separator.
     pub fn msg_separator(mut self, sep: impl Into<String>) -> Self {
         self.msg_separator = sep.into();
@@ -355,22 +1133,89 @@ impl OptsBuilder {
     ///
     /// # Errors
     ///
-    /// Returns an error if a custom timestamp format string is invalid.
+    /// Returns an error if the timestamp format string is invalid, if
+    /// the timezone offset or name is invalid, if the caller path keeps zero
+    /// components, if a static field has an empty name, if a redaction
+    /// pattern is invalid or the `regex` feature is missing, if hashed
+    /// target colours are enabled with an empty palette, if the rate limit
+    /// allows no records or has an empty interval, or if a length limit is
+    /// zero.
     pub fn build(self) -> Result<Opts> {
-        // Validate custom timestamp format if provided
-        if let TSFormat::Custom(ref fmt) = self.timestamp_format {
-            validate_time_format(fmt)?;
+        // Validate the timestamp format, presets included
+        validate_time_format(self.timestamp_format.to_format_string())?;
+
+        self.timezone.resolve()?;
+
+        if self.caller_path == CallerPath::LastComponents(0) {
+            return Err(TwygError::ConfigError(
+                "caller path must keep at least one component".to_string(),
+            ));
+        }
+
+        if self.fields.keys().any(|key| key.is_empty()) {
+            return Err(TwygError::ConfigError(
+                "static field names must not be empty".to_string(),
+            ));
+        }
+
+        self.redaction.compile()?;
+
+        if self.colors.target_hashed && self.colors.target_palette.is_empty() {
+            return Err(TwygError::ConfigError(
+                "target palette must not be empty when target_hashed is set".to_string(),
+            ));
+        }
+
+        if let Some(limit) = &self.rate_limit {
+            limit.compile()?;
         }
 
+        validate_len_limit("max_value_len", self.max_value_len)?;
+        validate_len_limit("max_message_len", self.max_message_len)?;
+        validate_len_limit("max_line_len", self.max_line_len)?;
+
         Ok(Opts {
             coloured: self.coloured,
+            file_coloured: self.file_coloured,
             output: self.output,
             level: self.level,
             report_caller: self.report_caller,
+            report_column: self.report_column,
+            caller_path: self.caller_path,
             timestamp_format: self.timestamp_format,
+            timezone: self.timezone,
+            clock: self.clock,
             pad_level: self.pad_level,
             pad_amount: self.pad_amount,
+            level_labels: self.level_labels,
             pad_side: self.pad_side,
+            target_format: self.target_format,
+            pad_target: self.pad_target,
+            target_pad_amount: self.target_pad_amount,
+            target_pad_side: self.target_pad_side,
+            target_truncate_side: self.target_truncate_side,
+            report_thread: self.report_thread,
+            thread_format: self.thread_format,
+            pad_thread: self.pad_thread,
+            thread_pad_amount: self.thread_pad_amount,
+            thread_pad_side: self.thread_pad_side,
+            report_process: self.report_process,
+            process: self.process,
+            fields: self.fields,
+            redaction: self.redaction,
+            sanitize: self.sanitize,
+            newline_mode: self.newline_mode,
+            indent_multiline: self.indent_multiline,
+            gutter_char: self.gutter_char,
+            field_layout: self.field_layout,
+            value_brackets: self.value_brackets,
+            kv_separator: self.kv_separator,
+            pair_separator: self.pair_separator,
+            field_order: self.field_order,
+            max_value_len: self.max_value_len,
+            max_message_len: self.max_message_len,
+            max_line_len: self.max_line_len,
+            rate_limit: self.rate_limit,
             msg_separator: self.msg_separator,
             arrow_char: self.arrow_char,
             colors: self.colors,
@@ -378,6 +1223,17 @@ impl OptsBuilder {
     }
 }
 
+/// Validates a length limit: zero would leave nothing but the marker.
+pub(crate) fn validate_len_limit(name: &str, len: Option<usize>) -> Result<()> {
+    if len == Some(0) {
+        return Err(TwygError::ConfigError(format!(
+            "{} must be at least 1",
+            name
+        )));
+    }
+    Ok(())
+}
+
 /// Validates a time format string by attempting to format the current time.
 fn validate_time_format(format: &str) -> Result<()> {
     match std::panic::catch_unwind(|| {
@@ -604,6 +1460,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn test_pad_side_clone() {
         let left = PadSide::Left;
         let cloned = left.clone();
@@ -647,11 +1504,461 @@ mod tests {
         assert_eq!(opts.colors(), &colors);
     }
 
+    #[test]
+    fn test_file_coloured_defaults_to_false() {
+        assert!(!Opts::default().file_coloured());
+        assert!(!OptsBuilder::new().build().unwrap().file_coloured());
+    }
+
+    #[test]
+    fn test_opts_builder_file_coloured() {
+        let opts = OptsBuilder::new()
+            .coloured(true)
+            .file_coloured(true)
+            .output(Output::file("/tmp/test.log"))
+            .build()
+            .unwrap();
+        assert!(opts.coloured());
+        assert!(opts.file_coloured());
+    }
+
+    #[test]
+    fn test_opts_builder_report_column() {
+        assert!(!Opts::default().report_column());
+        let opts = OptsBuilder::new()
+            .report_caller(true)
+            .report_column(true)
+            .build()
+            .unwrap();
+        assert!(opts.report_column());
+    }
+
+    #[test]
+    fn test_opts_builder_caller_path() {
+        assert_eq!(Opts::default().caller_path(), &CallerPath::Full);
+        let opts = OptsBuilder::new()
+            .caller_path(CallerPath::Basename)
+            .build()
+            .unwrap();
+        assert_eq!(opts.caller_path(), &CallerPath::Basename);
+    }
+
+    #[test]
+    fn test_opts_builder_caller_path_zero_components_is_error() {
+        let result = OptsBuilder::new()
+            .caller_path(CallerPath::LastComponents(0))
+            .build();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_opts_builder_hashed_target_colors_need_palette() {
+        let colors = Colors {
+            target_hashed: true,
+            target_palette: Vec::new(),
+            ..Colors::default()
+        };
+        let result = OptsBuilder::new().colors(colors).build();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+
+        let colors = Colors {
+            target_hashed: true,
+            ..Colors::default()
+        };
+        assert!(OptsBuilder::new().colors(colors).build().is_ok());
+    }
+
+    #[test]
+    fn test_opts_builder_validates_all_timestamp_presets() {
+        for format in [
+            TSFormat::RFC3339,
+            TSFormat::RFC3339Utc,
+            TSFormat::RFC3339Millis,
+            TSFormat::RFC3339Micros,
+            TSFormat::RFC3339Nanos,
+            TSFormat::Standard,
+            TSFormat::Simple,
+            TSFormat::TimeOnly,
+            TSFormat::UnixSecs,
+            TSFormat::UnixMillis,
+            TSFormat::UnixNanos,
+            TSFormat::Elapsed,
+            TSFormat::Delta,
+            TSFormat::None,
+        ] {
+            let opts = OptsBuilder::new()
+                .timestamp_format(format.clone())
+                .build()
+                .unwrap();
+            assert_eq!(opts.timestamp_format(), &format);
+        }
+    }
+
+    #[test]
+    fn test_opts_timestamp_presets_from_toml() {
+        let opts: Opts = toml::from_str(r#"timestamp_format = "UnixMillis""#).unwrap();
+        assert_eq!(opts.timestamp_format(), &TSFormat::UnixMillis);
+
+        let opts: Opts = toml::from_str(r#"timestamp_format = "None""#).unwrap();
+        assert_eq!(opts.timestamp_format(), &TSFormat::None);
+        #[allow(deprecated)]
+        let time_format = opts.time_format();
+        assert_eq!(time_format, None);
+    }
+
+    #[test]
+    fn test_opts_builder_clock() {
+        use crate::clock::ManualClock;
+        use chrono::{TimeZone, Utc};
+
+        let now = Utc.with_ymd_and_hms(2026, 1, 15, 14, 30, 52).unwrap();
+        let opts = OptsBuilder::new()
+            .clock(Arc::new(ManualClock::new(now)))
+            .build()
+            .unwrap();
+        assert_eq!(opts.clock().now(), now);
+
+        // The clock isn't serialized; deserialized options use the system clock
+        let json = serde_json::to_string(&opts).unwrap();
+        assert!(!json.contains("clock"));
+        let deserialized: Opts = serde_json::from_str(&json).unwrap();
+        assert_ne!(deserialized.clock().now(), now);
+    }
+
+    #[test]
+    fn test_opts_builder_timezone() {
+        let opts = OptsBuilder::new()
+            .timezone(Timezone::Fixed("+05:30".to_string()))
+            .build()
+            .unwrap();
+        assert_eq!(opts.timezone(), &Timezone::Fixed("+05:30".to_string()));
+
+        let result = OptsBuilder::new()
+            .timezone(Timezone::Fixed("5:30".to_string()))
+            .build();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_opts_timezone_from_toml() {
+        let opts: Opts = toml::from_str(r#"timezone = "Utc""#).unwrap();
+        assert_eq!(opts.timezone(), &Timezone::Utc);
+
+        let opts: Opts = toml::from_str(r#"timezone = { Fixed = "-08:00" }"#).unwrap();
+        assert_eq!(opts.timezone(), &Timezone::Fixed("-08:00".to_string()));
+    }
+
+    #[test]
+    fn test_opts_builder_target_options() {
+        let opts = OptsBuilder::new()
+            .target_format(TargetFormat::Abbreviated)
+            .pad_target(true)
+            .target_pad_amount(12)
+            .target_pad_side(PadSide::Left)
+            .target_truncate_side(TruncateSide::Right)
+            .build()
+            .unwrap();
+        assert_eq!(opts.target_format(), TargetFormat::Abbreviated);
+        assert!(opts.pad_target());
+        assert_eq!(opts.target_pad_amount(), 12);
+        assert_eq!(opts.target_pad_side(), PadSide::Left);
+        assert_eq!(opts.target_truncate_side(), TruncateSide::Right);
+    }
+
+    #[test]
+    fn test_opts_builder_thread_options() {
+        let opts = OptsBuilder::new()
+            .report_thread(true)
+            .thread_format(ThreadFormat::NameAndId)
+            .pad_thread(true)
+            .thread_pad_amount(16)
+            .thread_pad_side(PadSide::Left)
+            .build()
+            .unwrap();
+        assert!(opts.report_thread());
+        assert_eq!(opts.thread_format(), ThreadFormat::NameAndId);
+        assert!(opts.pad_thread());
+        assert_eq!(opts.thread_pad_amount(), 16);
+        assert_eq!(opts.thread_pad_side(), PadSide::Left);
+    }
+
+    #[test]
+    fn test_opts_thread_options_from_toml() {
+        let opts: Opts = toml::from_str(
+            r#"
+            report_thread = true
+            thread_format = "Id"
+            pad_thread = true
+            "#,
+        )
+        .unwrap();
+        assert!(opts.report_thread());
+        assert_eq!(opts.thread_format(), ThreadFormat::Id);
+        assert!(opts.pad_thread());
+        assert_eq!(opts.thread_pad_amount(), 12);
+    }
+
+    #[test]
+    fn test_opts_builder_process() {
+        use crate::process::Hostname;
+
+        let process = ProcessInfo {
+            service: Some("billing-api".to_string()),
+            hostname: Hostname::Auto,
+            pid: true,
+            ..ProcessInfo::default()
+        };
+        let opts = OptsBuilder::new()
+            .report_process(true)
+            .process(process.clone())
+            .build()
+            .unwrap();
+        assert!(opts.report_process());
+        assert_eq!(opts.process(), &process);
+    }
+
+    #[test]
+    fn test_opts_process_from_toml() {
+        let opts: Opts = toml::from_str(
+            r#"
+            report_process = true
+
+            [process]
+            service = "billing-api"
+            version = "1.4.2"
+            pid = true
+            "#,
+        )
+        .unwrap();
+        assert!(opts.report_process());
+        assert_eq!(opts.process().service.as_deref(), Some("billing-api"));
+        assert_eq!(opts.process().version.as_deref(), Some("1.4.2"));
+        assert!(opts.process().pid);
+    }
+
+    #[test]
+    fn test_opts_builder_fields() {
+        let opts = OptsBuilder::new()
+            .field("region", "us-east-1")
+            .field("deployment", "blue")
+            .build()
+            .unwrap();
+        assert_eq!(
+            opts.fields(),
+            &BTreeMap::from([
+                ("deployment".to_string(), "blue".to_string()),
+                ("region".to_string(), "us-east-1".to_string()),
+            ])
+        );
+
+        // fields() replaces what field() added
+        let opts = OptsBuilder::new()
+            .field("region", "us-east-1")
+            .fields(BTreeMap::from([("zone".to_string(), "b".to_string())]))
+            .build()
+            .unwrap();
+        assert_eq!(opts.fields().len(), 1);
+        assert_eq!(opts.fields()["zone"], "b");
+    }
+
+    #[test]
+    fn test_opts_builder_rejects_empty_field_name() {
+        let result = OptsBuilder::new().field("", "value").build();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_opts_fields_from_toml() {
+        let opts: Opts = toml::from_str(
+            r#"
+            [fields]
+            region = "us-east-1"
+            deployment = "blue"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(opts.fields()["region"], "us-east-1");
+        assert_eq!(opts.fields()["deployment"], "blue");
+    }
+
+    #[test]
+    fn test_opts_builder_redaction() {
+        use crate::redact::MaskStyle;
+
+        let redaction = Redaction {
+            keys: vec!["password".to_string()],
+            style: MaskStyle::Hashed,
+            ..Redaction::default()
+        };
+        let opts = OptsBuilder::new()
+            .redaction(redaction.clone())
+            .build()
+            .unwrap();
+        assert_eq!(opts.redaction(), &redaction);
+    }
+
+    #[test]
+    fn test_opts_builder_rejects_unusable_redaction_pattern() {
+        // Invalid with the `regex` feature, unsupported without it
+        let result = OptsBuilder::new()
+            .redaction(Redaction {
+                patterns: vec!["(unclosed".to_string()],
+                ..Redaction::default()
+            })
+            .build();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_opts_redaction_from_toml() {
+        let opts: Opts = toml::from_str(
+            r#"
+            [redaction]
+            keys = ["password", "*_token"]
+            style = "Partial"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(opts.redaction().keys, vec!["password", "*_token"]);
+    }
+
+    #[test]
+    fn test_opts_builder_sanitize() {
+        let opts = OptsBuilder::new()
+            .sanitize(true)
+            .newline_mode(NewlineMode::Indent)
+            .build()
+            .unwrap();
+        assert!(opts.sanitize());
+        assert_eq!(opts.newline_mode(), NewlineMode::Indent);
+    }
+
+    #[test]
+    fn test_opts_sanitize_from_toml() {
+        let opts: Opts = toml::from_str(
+            r#"
+            sanitize = true
+            newline_mode = "Indent"
+            "#,
+        )
+        .unwrap();
+        assert!(opts.sanitize());
+        assert_eq!(opts.newline_mode(), NewlineMode::Indent);
+    }
+
+    #[test]
+    fn test_opts_builder_indent_multiline() {
+        let opts = OptsBuilder::new()
+            .indent_multiline(true)
+            .gutter_char("│")
+            .build()
+            .unwrap();
+        assert!(opts.indent_multiline());
+        assert_eq!(opts.gutter_char(), Some("│"));
+    }
+
+    #[test]
+    fn test_opts_field_layout_from_toml() {
+        let opts: Opts = toml::from_str(r#"field_layout = "Pretty""#).unwrap();
+        assert_eq!(opts.field_layout(), FieldLayout::Pretty);
+    }
+
+    #[test]
+    fn test_opts_kv_style_from_toml() {
+        let opts: Opts = toml::from_str(
+            r#"
+            value_brackets = "Quotes"
+            kv_separator = ":"
+            pair_separator = " "
+            field_order = "Alphabetical"
+            max_value_len = 40
+            "#,
+        )
+        .unwrap();
+        assert_eq!(opts.value_brackets(), ValueBrackets::Quotes);
+        assert_eq!(opts.kv_separator(), ":");
+        assert_eq!(opts.pair_separator(), " ");
+        assert_eq!(opts.field_order(), FieldOrder::Alphabetical);
+        assert_eq!(opts.max_value_len(), Some(40));
+    }
+
+    #[test]
+    fn test_opts_length_limits() {
+        let opts = OptsBuilder::new()
+            .max_message_len(4096)
+            .max_line_len(8192)
+            .build()
+            .unwrap();
+        assert_eq!(opts.max_message_len(), Some(4096));
+        assert_eq!(opts.max_line_len(), Some(8192));
+
+        let opts: Opts = toml::from_str("max_line_len = 1400").unwrap();
+        assert_eq!(opts.max_message_len(), None);
+        assert_eq!(opts.max_line_len(), Some(1400));
+    }
+
+    #[test]
+    fn test_opts_builder_zero_length_limits_are_errors() {
+        for builder in [
+            OptsBuilder::new().max_value_len(0),
+            OptsBuilder::new().max_message_len(0),
+            OptsBuilder::new().max_line_len(0),
+        ] {
+            assert!(matches!(builder.build(), Err(TwygError::ConfigError(_))));
+        }
+        assert!(OptsBuilder::new()
+            .max_value_len(1)
+            .max_message_len(1)
+            .max_line_len(1)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_opts_rate_limit() {
+        use crate::ratelimit::RateLimitKey;
+
+        let limit = RateLimit {
+            max_records: 100,
+            interval_ms: 1000,
+            per: RateLimitKey::Target,
+        };
+        let opts = OptsBuilder::new()
+            .rate_limit(limit.clone())
+            .build()
+            .unwrap();
+        assert_eq!(opts.rate_limit(), Some(&limit));
+
+        let opts: Opts = toml::from_str("rate_limit = { max_records = 5 }").unwrap();
+        let limit = opts.rate_limit().unwrap();
+        assert_eq!(limit.max_records, 5);
+        assert_eq!(limit.interval_ms, 1000);
+        assert_eq!(limit.per, RateLimitKey::CallSite);
+    }
+
+    #[test]
+    fn test_opts_builder_rate_limit_without_records_is_error() {
+        let result = OptsBuilder::new()
+            .rate_limit(RateLimit {
+                max_records: 0,
+                interval_ms: 1000,
+                per: Default::default(),
+            })
+            .build();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_truncate_side_default() {
+        assert_eq!(TruncateSide::default(), TruncateSide::Left);
+    }
+
     #[test]
     fn test_opts_builder_preset_with_level_padding() {
         let opts = OptsBuilder::with_level_padding().build().unwrap();
         assert!(opts.pad_level());
         assert_eq!(opts.pad_amount(), 5);
+        assert_eq!(opts.level_labels(), &LevelLabels::Standard);
         assert_eq!(opts.pad_side(), PadSide::Right);
     }
 
@@ -793,11 +2100,39 @@ mod tests {
 
     #[test]
     fn test_default_helper_functions() {
-        assert_eq!(default_pad_amount(), 5);
+        assert_eq!(default_target_pad_amount(), 20);
         assert_eq!(default_msg_separator(), ": ");
         assert_eq!(default_arrow_char(), "▶");
     }
 
+    #[test]
+    fn test_opts_level_width_fits_level_labels() {
+        let opts: Opts = toml::from_str(r#"level_labels = "Letter""#).unwrap();
+        assert_eq!(opts.pad_amount(), 1);
+        assert_eq!(opts.level_width(), 1);
+
+        let opts = OptsBuilder::new().build().unwrap();
+        assert_eq!(opts.pad_amount(), 5);
+
+        let opts: Opts = toml::from_str(
+            r#"
+            pad_amount = 4
+            level_labels = { Custom = { error = "FAILURE" } }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(opts.pad_amount(), 4);
+        assert_eq!(opts.level_width(), 7);
+        assert_eq!(opts.level_labels().label(log::Level::Error), "FAILURE");
+
+        let opts = OptsBuilder::new()
+            .pad_amount(2)
+            .level_labels(LevelLabels::Icons)
+            .build()
+            .unwrap();
+        assert_eq!(opts.level_width(), 2);
+    }
+
     #[test]
     fn test_opts_deserialize_partial_toml_uses_defaults() {
         let toml_str = r#"level = "debug""#;
@@ -808,12 +2143,44 @@ mod tests {
 
         // All missing fields should have their default values.
         assert!(!opts.coloured());
+        assert!(!opts.file_coloured());
         assert_eq!(opts.output(), &Output::Stdout);
         assert!(!opts.report_caller());
+        assert!(!opts.report_column());
+        assert_eq!(opts.caller_path(), &CallerPath::Full);
         assert_eq!(opts.timestamp_format(), &TSFormat::Standard);
+        assert_eq!(opts.timezone(), &Timezone::Local);
         assert!(!opts.pad_level());
         assert_eq!(opts.pad_amount(), 5);
+        assert_eq!(opts.level_labels(), &LevelLabels::Standard);
         assert_eq!(opts.pad_side(), PadSide::Right);
+        assert_eq!(opts.target_format(), TargetFormat::Full);
+        assert!(!opts.pad_target());
+        assert_eq!(opts.target_pad_amount(), 20);
+        assert_eq!(opts.target_pad_side(), PadSide::Right);
+        assert_eq!(opts.target_truncate_side(), TruncateSide::Left);
+        assert!(!opts.report_thread());
+        assert_eq!(opts.thread_format(), ThreadFormat::Name);
+        assert!(!opts.pad_thread());
+        assert_eq!(opts.thread_pad_amount(), 12);
+        assert_eq!(opts.thread_pad_side(), PadSide::Right);
+        assert!(!opts.report_process());
+        assert_eq!(opts.process(), &ProcessInfo::default());
+        assert!(opts.fields().is_empty());
+        assert_eq!(opts.redaction(), &Redaction::default());
+        assert!(!opts.sanitize());
+        assert_eq!(opts.newline_mode(), NewlineMode::Escape);
+        assert!(!opts.indent_multiline());
+        assert_eq!(opts.gutter_char(), None);
+        assert_eq!(opts.field_layout(), FieldLayout::Inline);
+        assert_eq!(opts.value_brackets(), ValueBrackets::Braces);
+        assert_eq!(opts.kv_separator(), "=");
+        assert_eq!(opts.pair_separator(), ", ");
+        assert_eq!(opts.field_order(), FieldOrder::Insertion);
+        assert_eq!(opts.max_value_len(), None);
+        assert_eq!(opts.max_message_len(), None);
+        assert_eq!(opts.max_line_len(), None);
+        assert_eq!(opts.rate_limit(), None);
         assert_eq!(opts.msg_separator(), ": ");
         assert_eq!(opts.arrow_char(), "▶");
     }
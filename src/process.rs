@@ -0,0 +1,201 @@
+//! Process metadata attached to every record.
+//!
+//! This module provides [`ProcessInfo`], static details about the running
+//! process (service name, version, environment, hostname and pid) that are
+//! resolved once at setup and shown as fields on every line when
+//! `report_process` is enabled.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+/// Environment variables checked for the hostname, in order.
+const HOSTNAME_VARS: [&str; 2] = ["HOSTNAME", "COMPUTERNAME"];
+
+/// Files checked for the hostname when no variable is set, in order.
+const HOSTNAME_FILES: [&str; 2] = ["/proc/sys/kernel/hostname", "/etc/hostname"];
+
+/// Static metadata identifying the process that wrote a log line.
+///
+/// Fields that are unset are left out of the output.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{Hostname, OptsBuilder, ProcessInfo};
+///
+/// let opts = OptsBuilder::new()
+///     .report_process(true)
+///     .process(ProcessInfo {
+///         service: Some("billing-api".to_string()),
+///         version: Some(env!("CARGO_PKG_VERSION").to_string()),
+///         environment: Some("production".to_string()),
+///         hostname: Hostname::Auto,
+///         pid: true,
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    /// Service or application name, shown as `service`
+    #[serde(default)]
+    pub service: Option<String>,
+
+    /// Service version, shown as `version`
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// Deployment environment, e.g. "staging", shown as `env`
+    #[serde(default)]
+    pub environment: Option<String>,
+
+    /// Host name, shown as `host`
+    #[serde(default)]
+    pub hostname: Hostname,
+
+    /// Include the process ID, shown as `pid`
+    #[serde(default)]
+    pub pid: bool,
+}
+
+/// Where the hostname in [`ProcessInfo`] comes from.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hostname {
+    /// No hostname
+    #[default]
+    None,
+
+    /// Detected from the `HOSTNAME` or `COMPUTERNAME` environment variable,
+    /// the system hostname file, or else the operating system, e.g. on
+    /// macOS; left out if none is found
+    Auto,
+
+    /// A fixed name: `Fixed("web-01")`
+    Fixed(String),
+}
+
+impl Hostname {
+    /// Returns the hostname, detecting it for `Auto`.
+    pub fn resolve(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Self::None => None,
+            Self::Auto => detect_hostname().map(Cow::Owned),
+            Self::Fixed(name) => Some(Cow::Borrowed(name)),
+        }
+    }
+}
+
+fn detect_hostname() -> Option<String> {
+    let from_vars = HOSTNAME_VARS
+        .iter()
+        .filter_map(|var| std::env::var(var).ok());
+    let from_files = HOSTNAME_FILES
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok());
+
+    // gethostname(2) where there is no hostname file, e.g. on macOS
+    let from_system =
+        std::iter::once_with(|| gethostname::gethostname().to_string_lossy().into_owned());
+
+    from_vars
+        .chain(from_files)
+        .chain(from_system)
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+}
+
+impl ProcessInfo {
+    /// Resolves the metadata into the fields shown on each line.
+    pub(crate) fn fields(&self) -> ProcessFields {
+        let strings = [
+            ("service", self.service.clone()),
+            ("version", self.version.clone()),
+            ("env", self.environment.clone()),
+            ("host", self.hostname.resolve().map(Cow::into_owned)),
+        ];
+        ProcessFields {
+            strings: strings
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, value?)))
+                .collect(),
+            pid: self.pid.then(std::process::id),
+        }
+    }
+}
+
+/// Process metadata resolved once at setup.
+#[derive(Debug, Default)]
+pub(crate) struct ProcessFields {
+    pub(crate) strings: Vec<(&'static str, String)>,
+    pub(crate) pid: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_info_default_is_empty() {
+        let info = ProcessInfo::default();
+        assert_eq!(info.hostname, Hostname::None);
+        assert!(!info.pid);
+        let fields = info.fields();
+        assert!(fields.strings.is_empty());
+        assert_eq!(fields.pid, None);
+    }
+
+    #[test]
+    fn test_process_info_fields() {
+        let info = ProcessInfo {
+            service: Some("billing-api".to_string()),
+            version: Some("1.4.2".to_string()),
+            environment: None,
+            hostname: Hostname::Fixed("web-01".to_string()),
+            pid: true,
+        };
+        let fields = info.fields();
+        assert_eq!(
+            fields.strings,
+            vec![
+                ("service", "billing-api".to_string()),
+                ("version", "1.4.2".to_string()),
+                ("host", "web-01".to_string()),
+            ]
+        );
+        assert_eq!(fields.pid, Some(std::process::id()));
+    }
+
+    #[test]
+    fn test_hostname_resolve() {
+        assert_eq!(Hostname::None.resolve(), None);
+        assert_eq!(
+            Hostname::Fixed("web-01".to_string()).resolve().as_deref(),
+            Some("web-01")
+        );
+        let name = Hostname::Auto.resolve().unwrap();
+        assert!(!name.is_empty());
+        assert_eq!(name.trim(), name);
+    }
+
+    #[test]
+    fn test_process_info_from_toml() {
+        let info: ProcessInfo = toml::from_str(
+            r#"
+            service = "billing-api"
+            environment = "staging"
+            hostname = { Fixed = "web-01" }
+            pid = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(info.service.as_deref(), Some("billing-api"));
+        assert_eq!(info.version, None);
+        assert_eq!(info.environment.as_deref(), Some("staging"));
+        assert_eq!(info.hostname, Hostname::Fixed("web-01".to_string()));
+        assert!(info.pid);
+
+        let info: ProcessInfo = toml::from_str(r#"hostname = "Auto""#).unwrap();
+        assert_eq!(info.hostname, Hostname::Auto);
+    }
+}
@@ -0,0 +1,461 @@
+//! Rate limiting of repetitive records.
+//!
+//! This module provides [`RateLimit`], which caps how many records each call
+//! site or target may write per interval. Records over the cap are dropped
+//! and counted, and when the call site or target next writes after its
+//! interval, a summary such as "suppressed 1,234 similar messages" is
+//! written first, so a runaway loop can't flood the output. Counts that are
+//! still pending when the logger is flushed, or when an idle key is pruned,
+//! are summarised then instead.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{Level, Record};
+use serde::{Deserialize, Serialize};
+
+use super::error::{Result, TwygError};
+
+/// Keys tracked before idle windows are pruned.
+const PRUNE_THRESHOLD: usize = 4096;
+
+/// Independently locked parts of a limiter's keys, so records from
+/// different call sites rarely wait on each other.
+const SHARDS: usize = 16;
+
+/// How many records may be written per call site or target.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{OptsBuilder, RateLimit, RateLimitKey};
+///
+/// let opts = OptsBuilder::new()
+///     .rate_limit(RateLimit {
+///         max_records: 100,
+///         interval_ms: 1000,
+///         per: RateLimitKey::CallSite,
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Records written per key in each interval; the rest are suppressed
+    pub max_records: u32,
+
+    /// Length of the interval, in milliseconds (default: 1000)
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+
+    /// What records are counted together (default: CallSite)
+    #[serde(default)]
+    pub per: RateLimitKey,
+}
+
+/// What records a [`RateLimit`] counts together.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateLimitKey {
+    /// Records logged from the same file and line
+    #[default]
+    CallSite,
+
+    /// Records with the same target
+    Target,
+}
+
+fn default_interval_ms() -> u64 {
+    1000
+}
+
+impl RateLimit {
+    /// Validates the rate limit and creates its limiter.
+    pub(crate) fn compile(&self) -> Result<RateLimiter> {
+        if self.max_records == 0 {
+            return Err(TwygError::ConfigError(
+                "rate_limit max_records must be at least 1".to_string(),
+            ));
+        }
+        if self.interval_ms == 0 {
+            return Err(TwygError::ConfigError(
+                "rate_limit interval_ms must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(RateLimiter {
+            max_records: self.max_records,
+            interval: Duration::from_millis(self.interval_ms),
+            per: self.per,
+            shards: std::array::from_fn(|_| Mutex::new(State::default())),
+        })
+    }
+}
+
+/// A compiled [`RateLimit`], tracking the records written per key.
+pub(crate) struct RateLimiter {
+    max_records: u32,
+    interval: Duration,
+    per: RateLimitKey,
+    /// Each key's window lives in the shard its hash picks.
+    shards: [Mutex<State>; SHARDS],
+}
+
+#[derive(Default)]
+struct State {
+    windows: HashMap<String, Window>,
+    /// Scratch buffer the current record's key is built in.
+    key: String,
+}
+
+/// The current interval of one key.
+struct Window {
+    /// Monotonic clock reading at the start of the interval.
+    start: Duration,
+    written: u32,
+    suppressed: u64,
+    /// Where the first suppressed record came from, for summaries written
+    /// without a following record.
+    origin: Option<Origin>,
+}
+
+/// The level, target and caller of a suppressed record.
+struct Origin {
+    level: Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+/// Records suppressed under one key, summarised without a following record.
+pub(crate) struct Suppressed {
+    origin: Origin,
+    pub(crate) count: u64,
+}
+
+impl Suppressed {
+    /// Runs `f` with an empty record carrying the level, target and caller
+    /// of the first suppressed record.
+    pub(crate) fn with_record<R>(&self, f: impl FnOnce(&Record) -> R) -> R {
+        let origin = &self.origin;
+        f(&Record::builder()
+            .level(origin.level)
+            .target(&origin.target)
+            .module_path(origin.module_path.as_deref())
+            .file(origin.file.as_deref())
+            .line(origin.line)
+            .args(format_args!(""))
+            .build())
+    }
+}
+
+impl Window {
+    /// Takes the pending suppressed count, if any, as a summary.
+    fn take_suppressed(&mut self) -> Option<Suppressed> {
+        let count = std::mem::take(&mut self.suppressed);
+        let origin = self.origin.take()?;
+        (count > 0).then_some(Suppressed { origin, count })
+    }
+}
+
+impl RateLimiter {
+    /// Counts `record` against its key at monotonic time `now`.
+    ///
+    /// Returns `None` when the record is suppressed, or else the number of
+    /// records suppressed since its key last wrote, to be reported first.
+    /// Idle keys pruned along the way add their pending counts to `pruned`.
+    pub(crate) fn check(
+        &self,
+        record: &Record,
+        now: Duration,
+        pruned: &mut Vec<Suppressed>,
+    ) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        match self.per {
+            RateLimitKey::CallSite => {
+                record.file().unwrap_or(record.target()).hash(&mut hasher);
+                record.line().unwrap_or(0).hash(&mut hasher);
+            }
+            RateLimitKey::Target => record.target().hash(&mut hasher),
+        }
+        let shard = &self.shards[hasher.finish() as usize % SHARDS];
+        let mut state = shard.lock().unwrap_or_else(|e| e.into_inner());
+        let State { windows, key } = &mut *state;
+
+        key.clear();
+        match self.per {
+            RateLimitKey::CallSite => {
+                let file = record.file().unwrap_or(record.target());
+                let _ = write!(key, "{}:{}", file, record.line().unwrap_or(0));
+            }
+            RateLimitKey::Target => key.push_str(record.target()),
+        }
+
+        if !windows.contains_key(key.as_str()) {
+            if windows.len() >= PRUNE_THRESHOLD / SHARDS {
+                windows.retain(|_, window| {
+                    if now.saturating_sub(window.start) < self.interval {
+                        return true;
+                    }
+                    pruned.extend(window.take_suppressed());
+                    false
+                });
+            }
+            windows.insert(
+                key.clone(),
+                Window {
+                    start: now,
+                    written: 0,
+                    suppressed: 0,
+                    origin: None,
+                },
+            );
+        }
+        let window = windows.get_mut(key.as_str())?;
+
+        let mut reported = 0;
+        if now.saturating_sub(window.start) >= self.interval {
+            reported = std::mem::take(&mut window.suppressed);
+            window.origin = None;
+            window.start = now;
+            window.written = 0;
+        }
+
+        if window.written < self.max_records {
+            window.written += 1;
+            Some(reported)
+        } else {
+            if window.suppressed == 0 {
+                window.origin = Some(Origin {
+                    level: record.level(),
+                    target: record.target().to_string(),
+                    module_path: record.module_path().map(str::to_string),
+                    file: record.file().map(str::to_string),
+                    line: record.line(),
+                });
+            }
+            window.suppressed += 1;
+            None
+        }
+    }
+
+    /// Takes every key's pending suppressed count, e.g. when the logger is
+    /// flushed. Keys stay limited for the rest of their interval.
+    pub(crate) fn drain(&self) -> Vec<Suppressed> {
+        let mut drained = Vec::new();
+        for shard in &self.shards {
+            let mut state = shard.lock().unwrap_or_else(|e| e.into_inner());
+            drained.extend(
+                state
+                    .windows
+                    .values_mut()
+                    .filter_map(Window::take_suppressed),
+            );
+        }
+        drained
+    }
+}
+
+/// A count written with thousands separators: "1,234".
+pub(crate) struct Grouped(pub(crate) u64);
+
+impl fmt::Display for Grouped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.0.to_string();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                f.write_char(',')?;
+            }
+            f.write_char(digit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(max_records: u32, per: RateLimitKey) -> RateLimiter {
+        RateLimit {
+            max_records,
+            interval_ms: 1000,
+            per,
+        }
+        .compile()
+        .unwrap()
+    }
+
+    fn check_at(limiter: &RateLimiter, line: u32, target: &str, millis: u64) -> Option<u64> {
+        let mut pruned = Vec::new();
+        let checked = check_pruning(limiter, line, target, millis, &mut pruned);
+        assert!(pruned.is_empty());
+        checked
+    }
+
+    fn check_pruning(
+        limiter: &RateLimiter,
+        line: u32,
+        target: &str,
+        millis: u64,
+        pruned: &mut Vec<Suppressed>,
+    ) -> Option<u64> {
+        limiter.check(
+            &Record::builder()
+                .level(Level::Warn)
+                .target(target)
+                .file(Some("src/retry.rs"))
+                .line(Some(line))
+                .args(format_args!("connection refused"))
+                .build(),
+            Duration::from_millis(millis),
+            pruned,
+        )
+    }
+
+    fn windows(limiter: &RateLimiter) -> usize {
+        limiter
+            .shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().windows.len())
+            .sum()
+    }
+
+    #[test]
+    fn test_rate_limit_compile_rejects_zero() {
+        let limit = RateLimit {
+            max_records: 0,
+            interval_ms: 1000,
+            per: RateLimitKey::CallSite,
+        };
+        assert!(matches!(limit.compile(), Err(TwygError::ConfigError(_))));
+
+        let limit = RateLimit {
+            max_records: 1,
+            interval_ms: 0,
+            per: RateLimitKey::CallSite,
+        };
+        assert!(matches!(limit.compile(), Err(TwygError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_rate_limiter_suppresses_and_reports() {
+        let limiter = limiter(2, RateLimitKey::CallSite);
+        assert_eq!(check_at(&limiter, 10, "app", 0), Some(0));
+        assert_eq!(check_at(&limiter, 10, "app", 100), Some(0));
+        assert_eq!(check_at(&limiter, 10, "app", 200), None);
+        assert_eq!(check_at(&limiter, 10, "app", 999), None);
+
+        // The next interval starts with the first record after it
+        assert_eq!(check_at(&limiter, 10, "app", 1000), Some(2));
+        assert_eq!(check_at(&limiter, 10, "app", 1001), Some(0));
+        assert_eq!(check_at(&limiter, 10, "app", 1002), None);
+        assert_eq!(check_at(&limiter, 10, "app", 5000), Some(1));
+    }
+
+    #[test]
+    fn test_rate_limiter_per_call_site() {
+        let limiter = limiter(1, RateLimitKey::CallSite);
+        assert_eq!(check_at(&limiter, 10, "app", 0), Some(0));
+        assert_eq!(check_at(&limiter, 10, "app", 1), None);
+        assert_eq!(check_at(&limiter, 11, "app", 2), Some(0));
+    }
+
+    #[test]
+    fn test_rate_limiter_per_target() {
+        let limiter = limiter(1, RateLimitKey::Target);
+        assert_eq!(check_at(&limiter, 10, "app", 0), Some(0));
+        assert_eq!(check_at(&limiter, 11, "app", 1), None);
+        assert_eq!(check_at(&limiter, 10, "app::db", 2), Some(0));
+    }
+
+    #[test]
+    fn test_rate_limiter_prunes_idle_keys() {
+        let limiter = limiter(1, RateLimitKey::CallSite);
+        for line in 0..PRUNE_THRESHOLD as u32 {
+            check_at(&limiter, line, "app", 0);
+        }
+        assert_eq!(check_at(&limiter, 0, "app", 1), None);
+
+        // Once the old keys are idle, new ones push them out, and a pruned
+        // key's suppressed count is handed back to be reported
+        let mut pruned = Vec::new();
+        for line in 0..PRUNE_THRESHOLD as u32 {
+            check_pruning(&limiter, 100_000 + line, "app", 2000, &mut pruned);
+        }
+        assert!(windows(&limiter) < 2 * PRUNE_THRESHOLD);
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].count, 1);
+        pruned[0].with_record(|record| {
+            assert_eq!(record.level(), Level::Warn);
+            assert_eq!(record.file(), Some("src/retry.rs"));
+            assert_eq!(record.line(), Some(0));
+        });
+    }
+
+    #[test]
+    fn test_rate_limiter_drain() {
+        let limiter = limiter(1, RateLimitKey::Target);
+        assert_eq!(check_at(&limiter, 10, "app", 0), Some(0));
+        assert_eq!(check_at(&limiter, 11, "app", 1), None);
+        assert_eq!(check_at(&limiter, 12, "app", 2), None);
+        assert_eq!(check_at(&limiter, 10, "quiet", 3), Some(0));
+
+        let drained = limiter.drain();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].count, 2);
+        drained[0].with_record(|record| {
+            assert_eq!(record.target(), "app");
+            assert_eq!(record.line(), Some(11));
+        });
+
+        // Drained counts aren't reported again, and the interval still holds
+        assert!(limiter.drain().is_empty());
+        assert_eq!(check_at(&limiter, 10, "app", 4), None);
+        assert_eq!(check_at(&limiter, 10, "app", 1000), Some(1));
+    }
+
+    #[test]
+    fn test_rate_limiter_shared_across_threads() {
+        let limiter = limiter(100, RateLimitKey::CallSite);
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let limiter = &limiter;
+                scope.spawn(move || {
+                    for i in 0..100 {
+                        check_at(limiter, i % 2, &format!("app{}", thread), 0);
+                    }
+                });
+            }
+        });
+        // Both call sites wrote their 100 and suppressed the other 100
+        let drained = limiter.drain();
+        assert_eq!(drained.len(), 2);
+        assert!(drained.iter().all(|suppressed| suppressed.count == 100));
+    }
+
+    #[test]
+    fn test_grouped() {
+        assert_eq!(Grouped(0).to_string(), "0");
+        assert_eq!(Grouped(999).to_string(), "999");
+        assert_eq!(Grouped(1234).to_string(), "1,234");
+        assert_eq!(Grouped(40_000_000).to_string(), "40,000,000");
+    }
+
+    #[test]
+    fn test_rate_limit_serde() {
+        let limit: RateLimit = serde_json::from_str(r#"{"max_records": 5}"#).unwrap();
+        assert_eq!(limit.interval_ms, 1000);
+        assert_eq!(limit.per, RateLimitKey::CallSite);
+
+        let limit: RateLimit =
+            serde_json::from_str(r#"{"max_records": 5, "interval_ms": 60000, "per": "Target"}"#)
+                .unwrap();
+        assert_eq!(limit.per, RateLimitKey::Target);
+    }
+}
@@ -0,0 +1,409 @@
+//! Redaction of sensitive values.
+//!
+//! This module provides [`Redaction`], which masks the values of fields
+//! whose keys match configured names or globs, and with the `regex`
+//! feature, text in log messages that matches configured patterns. Masking
+//! happens as a record is rendered, so secrets never reach the output.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::color::fnv1a;
+use super::error::{Result, TwygError};
+
+/// Text shown in place of a masked value.
+const MASK: &str = "***";
+
+/// Characters kept at the end of a value by [`MaskStyle::Partial`].
+const PARTIAL_KEEP: usize = 4;
+
+/// Shortest value [`MaskStyle::Partial`] keeps any characters of.
+const PARTIAL_MIN_LEN: usize = 2 * PARTIAL_KEEP;
+
+/// Which values are masked, and how.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{MaskStyle, OptsBuilder, Redaction};
+///
+/// let opts = OptsBuilder::new()
+///     .redaction(Redaction {
+///         keys: vec!["password".into(), "*_token".into(), "authorization".into()],
+///         style: MaskStyle::Partial,
+///         ..Redaction::default()
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redaction {
+    /// Field keys whose values are masked, matched case-insensitively.
+    /// `*` matches any run of characters and `?` any single one:
+    /// "password", "*_token"
+    #[serde(default)]
+    pub keys: Vec<String>,
+
+    /// Regular expressions for text masked in messages, e.g. card numbers
+    /// or bearer tokens (requires the `regex` feature)
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// How masked values are shown (default: Full)
+    #[serde(default)]
+    pub style: MaskStyle,
+}
+
+/// How a masked value is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MaskStyle {
+    /// The whole value: "***"
+    #[default]
+    Full,
+
+    /// All but the last four characters: "***4242"; values shorter than
+    /// eight characters are masked fully
+    Partial,
+
+    /// A hash of the value, so equal values can be correlated without
+    /// showing them: "#0f3e7c2a9d41b856". The hash is not cryptographic;
+    /// short or guessable values can be recovered by brute force
+    Hashed,
+}
+
+impl Redaction {
+    /// Validates the redaction and compiles its patterns.
+    pub(crate) fn compile(&self) -> Result<Redactor> {
+        #[cfg(not(feature = "regex"))]
+        if let Some(pattern) = self.patterns.first() {
+            return Err(TwygError::ConfigError(format!(
+                "redaction pattern '{}' requires the `regex` feature",
+                pattern
+            )));
+        }
+
+        Ok(Redactor {
+            keys: self.keys.clone(),
+            #[cfg(feature = "regex")]
+            patterns: self
+                .patterns
+                .iter()
+                .map(|pattern| {
+                    regex::Regex::new(pattern).map_err(|e| {
+                        TwygError::ConfigError(format!(
+                            "invalid redaction pattern '{}': {}",
+                            pattern, e
+                        ))
+                    })
+                })
+                .collect::<Result<_>>()?,
+            style: self.style,
+        })
+    }
+}
+
+/// A compiled [`Redaction`], applied as records are rendered.
+#[derive(Debug, Default)]
+pub(crate) struct Redactor {
+    keys: Vec<String>,
+    #[cfg(feature = "regex")]
+    patterns: Vec<regex::Regex>,
+    style: MaskStyle,
+}
+
+impl Redactor {
+    /// Returns whether the value of the field `key` is masked.
+    pub(crate) fn redacts_key(&self, key: &str) -> bool {
+        self.keys
+            .iter()
+            .any(|pattern| glob_match(pattern.as_bytes(), key.as_bytes()))
+    }
+
+    /// Masks the values of redacted keys at any depth of a nested value,
+    /// returning whether any were masked.
+    #[cfg(feature = "kv_serde")]
+    pub(crate) fn redact_tree(&self, tree: &mut serde_json::Value) -> bool {
+        use serde_json::Value as Json;
+
+        let mut redacted = false;
+        match tree {
+            Json::Object(map) => {
+                for (name, child) in map.iter_mut() {
+                    if self.redacts_key(name) {
+                        let text = match &*child {
+                            Json::String(text) => text.clone(),
+                            other => other.to_string(),
+                        };
+                        *child = Json::String(self.mask(&text).to_string());
+                        redacted = true;
+                    } else {
+                        redacted |= self.redact_tree(child);
+                    }
+                }
+            }
+            Json::Array(items) => {
+                for child in items {
+                    redacted |= self.redact_tree(child);
+                }
+            }
+            _ => {}
+        }
+        redacted
+    }
+
+    /// Returns whether rendered text names a redacted key, so a value that
+    /// can't be walked, such as one captured with `Debug`, is masked whole.
+    pub(crate) fn mentions_key(&self, text: &str) -> bool {
+        !self.keys.is_empty()
+            && text
+                .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .any(|word| !word.is_empty() && self.redacts_key(word))
+    }
+
+    /// Returns whether messages are scanned for patterns.
+    pub(crate) fn redacts_messages(&self) -> bool {
+        #[cfg(feature = "regex")]
+        return !self.patterns.is_empty();
+        #[cfg(not(feature = "regex"))]
+        false
+    }
+
+    /// Masks every pattern match in a message.
+    #[cfg(feature = "regex")]
+    pub(crate) fn redact_message<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        let mut message = Cow::Borrowed(message);
+        for pattern in &self.patterns {
+            if let Cow::Owned(redacted) = pattern.replace_all(&message, |caps: &regex::Captures| {
+                self.mask(&caps[0]).to_string()
+            }) {
+                message = Cow::Owned(redacted);
+            }
+        }
+        message
+    }
+
+    /// Masks every pattern match in a message.
+    #[cfg(not(feature = "regex"))]
+    pub(crate) fn redact_message<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(message)
+    }
+
+    /// Returns the masked form of a value.
+    pub(crate) fn mask<'a>(&self, value: &'a str) -> Masked<'a> {
+        Masked {
+            style: self.style,
+            value,
+        }
+    }
+}
+
+/// A value shown in its masked form.
+pub(crate) struct Masked<'a> {
+    style: MaskStyle,
+    value: &'a str,
+}
+
+impl fmt::Display for Masked<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.style {
+            MaskStyle::Full => f.write_str(MASK),
+            MaskStyle::Partial => {
+                let len = self.value.chars().count();
+                if len < PARTIAL_MIN_LEN {
+                    return f.write_str(MASK);
+                }
+                let (start, _) = self
+                    .value
+                    .char_indices()
+                    .nth(len - PARTIAL_KEEP)
+                    .unwrap_or_default();
+                write!(f, "{}{}", MASK, &self.value[start..])
+            }
+            MaskStyle::Hashed => write!(f, "#{:016x}", fnv1a(self.value)),
+        }
+    }
+}
+
+/// Matches ASCII-case-insensitively with `*` (any run) and `?` (any byte).
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*`, and the text position it was tried at
+    let mut retry = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                retry = Some((p, t));
+            }
+            Some(&c) if c == b'?' || c.eq_ignore_ascii_case(&text[t]) => {
+                p += 1;
+                t += 1;
+            }
+            _ => match retry {
+                // Let the last `*` swallow one more byte
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    retry = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(keys: &[&str], style: MaskStyle) -> Redactor {
+        Redaction {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            style,
+            ..Redaction::default()
+        }
+        .compile()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"password", b"password"));
+        assert!(glob_match(b"password", b"Password"));
+        assert!(!glob_match(b"password", b"password2"));
+        assert!(glob_match(b"*_token", b"api_token"));
+        assert!(glob_match(b"*_token", b"_token"));
+        assert!(!glob_match(b"*_token", b"token"));
+        assert!(glob_match(b"*token*", b"refresh_token_id"));
+        assert!(glob_match(b"pin?", b"pin1"));
+        assert!(!glob_match(b"pin?", b"pin"));
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"a*b*c", b"aXbYbZc"));
+        assert!(!glob_match(b"a*b*c", b"aXbYbZ"));
+    }
+
+    #[test]
+    fn test_redactor_keys() {
+        let redactor = redactor(&["password", "*_token", "authorization"], MaskStyle::Full);
+        assert!(redactor.redacts_key("password"));
+        assert!(redactor.redacts_key("AUTHORIZATION"));
+        assert!(redactor.redacts_key("api_token"));
+        assert!(!redactor.redacts_key("user"));
+        assert!(!Redactor::default().redacts_key("password"));
+    }
+
+    #[test]
+    fn test_redactor_mentions_key() {
+        let redactor = redactor(&["password", "*_token"], MaskStyle::Full);
+        assert!(redactor.mentions_key(r#"Cfg { password: "hunter2" }"#));
+        assert!(redactor.mentions_key("[(\"api_token\", 1)]"));
+        assert!(!redactor.mentions_key("Cfg { passwords: 2 }"));
+        assert!(!Redactor::default().mentions_key("password"));
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_redactor_redact_tree() {
+        let redactor = redactor(&["password"], MaskStyle::Full);
+        let mut tree = serde_json::json!({
+            "user": "app",
+            "hosts": [{"password": 42}],
+            "db": {"password": "hunter2"},
+        });
+        assert!(redactor.redact_tree(&mut tree));
+        assert_eq!(
+            tree,
+            serde_json::json!({
+                "user": "app",
+                "hosts": [{"password": "***"}],
+                "db": {"password": "***"},
+            })
+        );
+
+        let mut clean = serde_json::json!({"user": "app"});
+        assert!(!redactor.redact_tree(&mut clean));
+    }
+
+    #[test]
+    fn test_mask_styles() {
+        let full = redactor(&[], MaskStyle::Full);
+        assert_eq!(full.mask("hunter2").to_string(), "***");
+
+        let partial = redactor(&[], MaskStyle::Partial);
+        assert_eq!(partial.mask("4111111111114242").to_string(), "***4242");
+        assert_eq!(partial.mask("hunter2").to_string(), "***");
+        assert_eq!(partial.mask("pässwörd").to_string(), "***wörd");
+
+        let hashed = redactor(&[], MaskStyle::Hashed);
+        let first = hashed.mask("hunter2").to_string();
+        assert_eq!(first.len(), 17);
+        assert!(first.starts_with('#'));
+        assert_eq!(hashed.mask("hunter2").to_string(), first);
+        assert_ne!(hashed.mask("hunter3").to_string(), first);
+    }
+
+    #[test]
+    fn test_redaction_from_toml() {
+        let redaction: Redaction = toml::from_str(
+            r#"
+            keys = ["password", "*_token"]
+            style = "Hashed"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(redaction.keys, vec!["password", "*_token"]);
+        assert!(redaction.patterns.is_empty());
+        assert_eq!(redaction.style, MaskStyle::Hashed);
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn test_patterns_require_regex_feature() {
+        let redaction = Redaction {
+            patterns: vec![r"\d{16}".to_string()],
+            ..Redaction::default()
+        };
+        assert!(matches!(
+            redaction.compile(),
+            Err(TwygError::ConfigError(_))
+        ));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_redact_message() {
+        let redactor = Redaction {
+            patterns: vec![r"\b\d{16}\b".to_string(), r"Bearer \S+".to_string()],
+            style: MaskStyle::Partial,
+            ..Redaction::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(redactor.redacts_messages());
+        assert_eq!(
+            redactor.redact_message("charged 4111111111114242 with Bearer abc.def.ghi"),
+            "charged ***4242 with ***.ghi"
+        );
+        assert!(matches!(
+            redactor.redact_message("nothing to hide"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_invalid_pattern() {
+        let redaction = Redaction {
+            patterns: vec!["(unclosed".to_string()],
+            ..Redaction::default()
+        };
+        assert!(matches!(
+            redaction.compile(),
+            Err(TwygError::ConfigError(_))
+        ));
+    }
+}
@@ -0,0 +1,343 @@
+//! Log injection protection.
+//!
+//! This module provides the [`NewlineMode`] enum and the display wrapper the
+//! formatter uses when `sanitize` is enabled: control characters, Unicode
+//! line separators and bidirectional overrides in messages, fields, targets
+//! and thread names are escaped and embedded ANSI escape sequences are
+//! stripped, so user-supplied text can't forge log lines, reorder them or
+//! repaint the terminal.
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+/// The single-character form of `ESC [`.
+const CSI: char = '\u{9b}';
+
+/// How newlines in messages and field values are written when sanitising.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{NewlineMode, OptsBuilder};
+///
+/// let opts = OptsBuilder::new()
+///     .sanitize(true)
+///     .newline_mode(NewlineMode::Indent)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NewlineMode {
+    /// Escape newlines so every record stays on one line: "a\nb"
+    #[default]
+    Escape,
+
+    /// Keep newlines, indenting each continuation line so it can't be
+    /// mistaken for a new record; see `indent_multiline` for aligning them
+    /// under the message
+    Indent,
+}
+
+/// Text written with control characters escaped and ANSI sequences removed.
+pub(crate) struct Sanitized<T> {
+    text: T,
+    newline_mode: NewlineMode,
+}
+
+impl<T: fmt::Display> Sanitized<T> {
+    pub(crate) fn new(text: T, newline_mode: NewlineMode) -> Self {
+        Sanitized { text, newline_mode }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Sanitized<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut writer = SanitizingWriter::new(f, self.newline_mode);
+        write!(writer, "{}", self.text)?;
+        writer.finish()
+    }
+}
+
+/// Where the writer is within an ANSI escape sequence, which may be split
+/// across writes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Text,
+    /// After ESC
+    Escape,
+    /// Inside a control sequence: `ESC [ ... final`
+    Csi,
+    /// Inside an operating system command: `ESC ] ... BEL` or `ESC ] ... ESC \`
+    Osc,
+    /// After ESC inside an operating system command
+    OscEscape,
+}
+
+struct SanitizingWriter<'a, W> {
+    inner: &'a mut W,
+    newline_mode: NewlineMode,
+    state: State,
+    /// What followed the introducer of the sequence being read, written as
+    /// text if the sequence never ends.
+    pending: String,
+}
+
+impl<W: fmt::Write> fmt::Write for SanitizingWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Pass runs of plain text through in one call
+        let mut plain = 0;
+        for (i, c) in s.char_indices() {
+            if self.state == State::Text && !needs_handling(c) {
+                continue;
+            }
+            self.inner.write_str(&s[plain..i])?;
+            plain = i + c.len_utf8();
+            self.handle(c)?;
+        }
+        self.inner.write_str(&s[plain..])
+    }
+}
+
+impl<'a, W: fmt::Write> SanitizingWriter<'a, W> {
+    fn new(inner: &'a mut W, newline_mode: NewlineMode) -> Self {
+        SanitizingWriter {
+            inner,
+            newline_mode,
+            state: State::Text,
+            pending: String::new(),
+        }
+    }
+
+    /// Ends the text. An unterminated sequence loses only its introducer:
+    /// the rest is written as sanitised text rather than silently dropped.
+    fn finish(&mut self) -> fmt::Result {
+        while self.state != State::Text {
+            self.state = State::Text;
+            let pending = std::mem::take(&mut self.pending);
+            self.write_str(&pending)?;
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, c: char) -> fmt::Result {
+        let state = match (self.state, c) {
+            (State::Text, ESC) => State::Escape,
+            (State::Text, CSI) => State::Csi,
+            (State::Text, '\n') => {
+                // The formatter indents continuation lines
+                match self.newline_mode {
+                    NewlineMode::Escape => self.inner.write_str("\\n")?,
+                    NewlineMode::Indent => self.inner.write_char('\n')?,
+                }
+                State::Text
+            }
+            (State::Text, c) => {
+                write!(self.inner, "{}", c.escape_default())?;
+                State::Text
+            }
+            (State::Escape, '[') => State::Csi,
+            (State::Escape, ']') => State::Osc,
+            // Two-character sequences, e.g. `ESC c` (reset)
+            (State::Escape, _) => State::Text,
+            // Parameter and intermediate bytes, up to the final byte
+            (State::Csi, '\u{20}'..='\u{3f}') => State::Csi,
+            (State::Csi, _) => State::Text,
+            (State::Osc, BEL) => State::Text,
+            (State::Osc, ESC) => State::OscEscape,
+            (State::Osc, _) => State::Osc,
+            (State::OscEscape, _) => State::Text,
+        };
+        match state {
+            State::Text => self.pending.clear(),
+            // Introducers aren't kept
+            _ if matches!(
+                (self.state, c),
+                (State::Text, _) | (State::Escape, '[' | ']')
+            ) => {}
+            _ => self.pending.push(c),
+        }
+        self.state = state;
+        Ok(())
+    }
+}
+
+/// Returns whether a character in plain text is escaped, or starts an
+/// escape sequence. Tabs are left as they are.
+///
+/// Besides control characters, this covers the Unicode line and paragraph
+/// separators, which some viewers break lines at, and the bidirectional
+/// embedding, override and isolate characters, which can visually reorder
+/// the rest of a line.
+fn needs_handling(c: char) -> bool {
+    (c.is_control() && c != '\t')
+        || matches!(
+            c,
+            '\u{2028}' | '\u{2029}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+        )
+}
+
+/// Returns a label such as a field key, target or thread name with control
+/// characters escaped, newlines included, and ANSI sequences removed,
+/// borrowing it when there is nothing to change.
+pub(crate) fn sanitize_label(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(needs_handling) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(Sanitized::new(text, NewlineMode::Escape).to_string())
+}
+
+/// Returns the number of terminal columns `s` takes, skipping ANSI escape
+/// sequences.
+pub(crate) fn visible_width(s: &str) -> usize {
+    struct Count(usize);
+    impl fmt::Write for Count {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.width();
+            Ok(())
+        }
+    }
+
+    let mut count = Count(0);
+    let mut writer = SanitizingWriter::new(&mut count, NewlineMode::Indent);
+    let _ = writer.write_str(s).and_then(|_| writer.finish());
+    count.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escape(text: &str) -> String {
+        Sanitized::new(text, NewlineMode::Escape).to_string()
+    }
+
+    fn indent(text: &str) -> String {
+        Sanitized::new(text, NewlineMode::Indent).to_string()
+    }
+
+    #[test]
+    fn test_newline_mode_default() {
+        assert_eq!(NewlineMode::default(), NewlineMode::Escape);
+    }
+
+    #[test]
+    fn test_plain_text_unchanged() {
+        assert_eq!(escape("user logged in"), "user logged in");
+        assert_eq!(escape("tab\tseparated"), "tab\tseparated");
+        assert_eq!(escape("ünïcödé ▶"), "ünïcödé ▶");
+    }
+
+    #[test]
+    fn test_newlines_escaped() {
+        assert_eq!(
+            escape("bad\n2026-01-15 14:30:52 INFO [app] ▶ forged"),
+            "bad\\n2026-01-15 14:30:52 INFO [app] ▶ forged"
+        );
+        assert_eq!(escape("over\rwrite"), "over\\rwrite");
+    }
+
+    #[test]
+    fn test_newlines_kept_for_indenting() {
+        assert_eq!(indent("first\nsecond\nthird"), "first\nsecond\nthird");
+        assert_eq!(indent("carriage\r\nreturn"), "carriage\\r\nreturn");
+    }
+
+    #[test]
+    fn test_control_characters_escaped() {
+        assert_eq!(escape("bell\u{7}"), "bell\\u{7}");
+        assert_eq!(escape("nul\0"), "nul\\u{0}");
+        assert_eq!(escape("del\u{7f}"), "del\\u{7f}");
+    }
+
+    #[test]
+    fn test_ansi_sequences_stripped() {
+        assert_eq!(escape("\u{1b}[31mred\u{1b}[0m text"), "red text");
+        assert_eq!(escape("\u{1b}[2J\u{1b}[1;1Hcleared"), "cleared");
+        assert_eq!(escape("\u{9b}31mred"), "red");
+        assert_eq!(escape("reset\u{1b}c"), "reset");
+        assert_eq!(
+            escape("\u{1b}]0;pwned\u{7}title \u{1b}]8;;http://x\u{1b}\\link"),
+            "title link"
+        );
+    }
+
+    #[test]
+    fn test_unterminated_sequences_keep_their_text() {
+        // Only the introducer of a sequence that never ends is dropped
+        assert_eq!(escape("cut\u{1b}["), "cut");
+        assert_eq!(escape("trailing\u{1b}"), "trailing");
+        assert_eq!(escape("\u{1b}[12"), "12");
+        assert_eq!(
+            escape("a\u{1b}]0;title and the rest\nforged"),
+            "a0;title and the rest\\nforged"
+        );
+        assert_eq!(escape("\u{1b}]8;;x\u{1b}"), "8;;x");
+        // Control characters in the recovered text are still escaped
+        assert_eq!(escape("\u{1b}]a\u{7f}b"), "a\\u{7f}b");
+        assert_eq!(sanitize_label("app\u{1b}]title"), "apptitle");
+        assert_eq!(visible_width("\u{1b}]abc"), 3);
+    }
+
+    #[test]
+    fn test_unicode_separators_and_bidi_controls_escaped() {
+        assert_eq!(escape("line\u{2028}sep"), "line\\u{2028}sep");
+        assert_eq!(escape("para\u{2029}sep"), "para\\u{2029}sep");
+        for c in ('\u{202a}'..='\u{202e}').chain('\u{2066}'..='\u{2069}') {
+            assert_eq!(
+                escape(&format!("a{}b", c)),
+                format!("a\\u{{{:x}}}b", c as u32)
+            );
+        }
+        assert_eq!(
+            sanitize_label("evil\u{202e}txt.exe"),
+            "evil\\u{202e}txt.exe"
+        );
+        // Other non-ASCII text is left alone
+        assert_eq!(
+            escape("\u{2027}\u{2030}\u{2065}\u{206a}"),
+            "\u{2027}\u{2030}\u{2065}\u{206a}"
+        );
+    }
+
+    #[test]
+    fn test_sequence_split_across_writes() {
+        struct Parts;
+        impl fmt::Display for Parts {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a\u{1b}")?;
+                f.write_str("[3")?;
+                f.write_str("1mb")
+            }
+        }
+        assert_eq!(Sanitized::new(Parts, NewlineMode::Escape).to_string(), "ab");
+    }
+
+    #[test]
+    fn test_sanitize_label() {
+        assert!(matches!(
+            sanitize_label("myapp::db"),
+            Cow::Borrowed("myapp::db")
+        ));
+        assert_eq!(sanitize_label("user\nid"), "user\\nid");
+        assert_eq!(sanitize_label("app\u{1b}[2J"), "app");
+    }
+
+    #[test]
+    fn test_visible_width() {
+        assert_eq!(visible_width(""), 0);
+        assert_eq!(visible_width("INFO [app] ▶ "), 13);
+        assert_eq!(visible_width("\u{1b}[32mINFO\u{1b}[0m [app] "), 11);
+        assert_eq!(visible_width("INFO [注文] 🚀 "), 15);
+    }
+
+    #[test]
+    fn test_newline_mode_serde() {
+        let mode: NewlineMode = serde_json::from_str(r#""Indent""#).unwrap();
+        assert_eq!(mode, NewlineMode::Indent);
+    }
+}
@@ -0,0 +1,127 @@
+//! Target display configuration.
+//!
+//! This module provides the [`TargetFormat`] enum, which controls how a
+//! record's target (usually its module path) is shown in the `[target]` column.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+/// Separator between target path segments.
+const SEGMENT_SEPARATOR: &str = "::";
+
+/// How a record's target is displayed.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{OptsBuilder, TargetFormat};
+///
+/// let opts = OptsBuilder::new()
+///     .target_format(TargetFormat::Abbreviated)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetFormat {
+    /// The full target: "myapp::services::billing::invoices"
+    #[default]
+    Full,
+
+    /// Only the last segment: "invoices"
+    LastSegment,
+
+    /// Every segment but the last shortened to its first character:
+    /// "m::s::b::invoices"
+    Abbreviated,
+}
+
+impl TargetFormat {
+    /// Returns the target text to display.
+    pub(crate) fn display<'a>(&self, target: &'a str) -> Cow<'a, str> {
+        match self {
+            Self::Full => Cow::Borrowed(target),
+            Self::LastSegment => {
+                Cow::Borrowed(target.rsplit(SEGMENT_SEPARATOR).next().unwrap_or(target))
+            }
+            Self::Abbreviated => abbreviate(target),
+        }
+    }
+}
+
+/// Shortens every segment but the last to its first character.
+fn abbreviate(target: &str) -> Cow<'_, str> {
+    let Some((init, last)) = target.rsplit_once(SEGMENT_SEPARATOR) else {
+        return Cow::Borrowed(target);
+    };
+
+    let mut abbreviated = String::with_capacity(target.len());
+    for segment in init.split(SEGMENT_SEPARATOR) {
+        abbreviated.extend(segment.chars().next());
+        abbreviated.push_str(SEGMENT_SEPARATOR);
+    }
+    abbreviated.push_str(last);
+    Cow::Owned(abbreviated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "myapp::services::billing::invoices";
+
+    #[test]
+    fn test_target_format_default() {
+        assert_eq!(TargetFormat::default(), TargetFormat::Full);
+    }
+
+    #[test]
+    fn test_target_format_full() {
+        assert_eq!(TargetFormat::Full.display(TARGET), TARGET);
+    }
+
+    #[test]
+    fn test_target_format_last_segment() {
+        assert_eq!(TargetFormat::LastSegment.display(TARGET), "invoices");
+        assert_eq!(TargetFormat::LastSegment.display("myapp"), "myapp");
+    }
+
+    #[test]
+    fn test_target_format_abbreviated() {
+        assert_eq!(
+            TargetFormat::Abbreviated.display(TARGET),
+            "m::s::b::invoices"
+        );
+        assert_eq!(TargetFormat::Abbreviated.display("myapp"), "myapp");
+        assert_eq!(TargetFormat::Abbreviated.display("a::b"), "a::b");
+    }
+
+    #[test]
+    fn test_target_format_borrows_when_unchanged() {
+        assert!(matches!(
+            TargetFormat::Full.display(TARGET),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            TargetFormat::LastSegment.display(TARGET),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            TargetFormat::Abbreviated.display("myapp"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_target_format_serialize_deserialize() {
+        for format in [
+            TargetFormat::Full,
+            TargetFormat::LastSegment,
+            TargetFormat::Abbreviated,
+        ] {
+            let serialized = serde_json::to_string(&format).unwrap();
+            let deserialized: TargetFormat = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(format, deserialized);
+        }
+    }
+}
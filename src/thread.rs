@@ -0,0 +1,221 @@
+//! Thread display configuration.
+//!
+//! This module provides the [`ThreadFormat`] enum, which controls how the
+//! logging thread is shown in the `[thread]` column when `report_thread` is
+//! enabled.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+use super::sanitize::sanitize_label;
+
+/// How the thread that logged a record is displayed.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{OptsBuilder, ThreadFormat};
+///
+/// let opts = OptsBuilder::new()
+///     .report_thread(true)
+///     .thread_format(ThreadFormat::NameAndId)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThreadFormat {
+    /// The thread name, or its ID for unnamed threads: "worker-1", "7"
+    #[default]
+    Name,
+
+    /// The thread ID only: "7"
+    Id,
+
+    /// The thread name and ID: "worker-1#7", or "7" for unnamed threads
+    NameAndId,
+}
+
+impl ThreadFormat {
+    /// Returns the current thread, displayed in this format.
+    pub(crate) fn current(self) -> CurrentThread {
+        CurrentThread {
+            format: self,
+            sanitize: false,
+        }
+    }
+}
+
+/// The calling thread's name and numeric ID, looked up once per thread.
+struct ThreadInfo {
+    name: Option<Box<str>>,
+    id: Box<str>,
+    name_and_id: Option<Box<str>>,
+}
+
+impl ThreadInfo {
+    fn current() -> Self {
+        let thread = std::thread::current();
+        // ThreadId only exposes its number through Debug: "ThreadId(7)"
+        let id = format!("{:?}", thread.id());
+        let id = id
+            .strip_prefix("ThreadId(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(&id);
+        ThreadInfo {
+            name: thread.name().map(Box::from),
+            id: Box::from(id),
+            name_and_id: thread.name().map(|name| format!("{}#{}", name, id).into()),
+        }
+    }
+}
+
+thread_local! {
+    static THREAD_INFO: ThreadInfo = ThreadInfo::current();
+}
+
+/// The calling thread, rendered in a [`ThreadFormat`].
+pub(crate) struct CurrentThread {
+    format: ThreadFormat,
+    sanitize: bool,
+}
+
+impl CurrentThread {
+    /// Escapes control characters and strips ANSI sequences in the thread
+    /// name when `sanitize` is set.
+    pub(crate) fn sanitized(self, sanitize: bool) -> Self {
+        CurrentThread { sanitize, ..self }
+    }
+
+    fn write(&self, info: &ThreadInfo, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.format {
+            ThreadFormat::Name => info.name.as_deref(),
+            ThreadFormat::Id => None,
+            ThreadFormat::NameAndId => info.name_and_id.as_deref(),
+        };
+        let label = label.unwrap_or(&info.id);
+        let label = if self.sanitize {
+            sanitize_label(label)
+        } else {
+            Cow::Borrowed(label)
+        };
+
+        // Pad by display width, so CJK thread names keep the column aligned
+        let fill = f.width().unwrap_or(0).saturating_sub(label.width());
+        match f.align() {
+            Some(fmt::Alignment::Right) => write!(f, "{:fill$}{}", "", label),
+            _ => write!(f, "{}{:fill$}", label, ""),
+        }
+    }
+}
+
+impl fmt::Display for CurrentThread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Thread-local storage is gone while the thread shuts down
+        THREAD_INFO
+            .try_with(|info| self.write(info, f))
+            .unwrap_or_else(|_| self.write(&ThreadInfo::current(), f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on_thread(name: Option<&str>, format: ThreadFormat) -> String {
+        let mut builder = std::thread::Builder::new();
+        if let Some(name) = name {
+            builder = builder.name(name.to_string());
+        }
+        builder
+            .spawn(move || format.current().to_string())
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_thread_format_default() {
+        assert_eq!(ThreadFormat::default(), ThreadFormat::Name);
+    }
+
+    #[test]
+    fn test_thread_format_name() {
+        assert_eq!(on_thread(Some("worker-1"), ThreadFormat::Name), "worker-1");
+    }
+
+    #[test]
+    fn test_thread_format_unnamed_falls_back_to_id() {
+        let id = on_thread(None, ThreadFormat::Name);
+        assert!(!id.is_empty());
+        assert!(id.chars().all(|c| c.is_ascii_digit()), "{id}");
+    }
+
+    #[test]
+    fn test_thread_format_id() {
+        let id = on_thread(Some("worker-1"), ThreadFormat::Id);
+        assert!(id.chars().all(|c| c.is_ascii_digit()), "{id}");
+    }
+
+    #[test]
+    fn test_thread_format_name_and_id() {
+        let label = on_thread(Some("worker-1"), ThreadFormat::NameAndId);
+        let (name, id) = label.split_once('#').unwrap();
+        assert_eq!(name, "worker-1");
+        assert!(id.chars().all(|c| c.is_ascii_digit()), "{id}");
+
+        let unnamed = on_thread(None, ThreadFormat::NameAndId);
+        assert!(unnamed.chars().all(|c| c.is_ascii_digit()), "{unnamed}");
+    }
+
+    #[test]
+    fn test_thread_format_padding() {
+        let padded = std::thread::Builder::new()
+            .name("w1".to_string())
+            .spawn(|| {
+                let current = ThreadFormat::Name.current();
+                format!("{:<6}|{:>6}", current, current)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(padded, "w1    |    w1");
+    }
+
+    #[test]
+    fn test_thread_format_padding_wide_name() {
+        let padded = std::thread::Builder::new()
+            .name("作業者".to_string())
+            .spawn(|| format!("{:<8}|", ThreadFormat::Name.current()))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(padded, "作業者  |");
+    }
+
+    #[test]
+    fn test_thread_format_sanitized() {
+        let labels = std::thread::Builder::new()
+            .name("w\u{1b}[31m1\nx".to_string())
+            .spawn(|| {
+                let current = || ThreadFormat::Name.current();
+                (
+                    format!("{:<6}|", current().sanitized(true)),
+                    current().sanitized(false).to_string(),
+                )
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(labels.0, "w1\\nx |");
+        assert_eq!(labels.1, "w\u{1b}[31m1\nx");
+    }
+
+    #[test]
+    fn test_thread_format_serde() {
+        let format: ThreadFormat = serde_json::from_str(r#""NameAndId""#).unwrap();
+        assert_eq!(format, ThreadFormat::NameAndId);
+    }
+}
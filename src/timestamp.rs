@@ -1,15 +1,56 @@
 //! Timestamp format configuration.
 //!
-//! This module provides timestamp format presets matching zylog's TSFormat enum.
+//! This module provides timestamp format presets matching zylog's TSFormat enum,
+//! and the [`Timezone`] timestamps are rendered in.
 
+use std::fmt;
+use std::time::Duration;
+
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::error::{Result, TwygError};
+
 /// Timestamp format presets.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TSFormat {
     /// RFC3339: "2006-01-02T15:04:05Z07:00"
     RFC3339,
 
+    /// RFC3339 in UTC: "2006-01-02T15:04:05Z"
+    ///
+    /// Always rendered in UTC, whatever the timezone setting.
+    RFC3339Utc,
+
+    /// RFC3339 with milliseconds: "2006-01-02T15:04:05.000-07:00"
+    RFC3339Millis,
+
+    /// RFC3339 with microseconds: "2006-01-02T15:04:05.000000-07:00"
+    RFC3339Micros,
+
+    /// RFC3339 with nanoseconds: "2006-01-02T15:04:05.000000000-07:00"
+    RFC3339Nanos,
+
+    /// Unix epoch seconds: "1136239445"
+    UnixSecs,
+
+    /// Unix epoch milliseconds: "1136239445000"
+    UnixMillis,
+
+    /// Unix epoch nanoseconds: "1136239445000000000"
+    UnixNanos,
+
+    /// Time elapsed since logger setup, on a monotonic clock: "+12.345s"
+    Elapsed,
+
+    /// Time elapsed since the previous record, on a monotonic clock: "Δ3ms"
+    Delta,
+
+    /// No timestamp, for environments such as systemd or Kubernetes that
+    /// already stamp each line
+    None,
+
     /// Standard: "2006-01-02 15:04:05"
     #[default]
     Standard,
@@ -25,10 +66,19 @@ pub enum TSFormat {
 }
 
 impl TSFormat {
-    /// Convert to chrono format string
+    /// Convert to chrono format string (empty for [`TSFormat::Elapsed`],
+    /// [`TSFormat::Delta`] and [`TSFormat::None`], which don't use chrono)
     pub fn to_format_string(&self) -> &str {
         match self {
             Self::RFC3339 => "%Y-%m-%dT%H:%M:%S%z",
+            Self::RFC3339Utc => "%Y-%m-%dT%H:%M:%SZ",
+            Self::RFC3339Millis => "%Y-%m-%dT%H:%M:%S%.3f%:z",
+            Self::RFC3339Micros => "%Y-%m-%dT%H:%M:%S%.6f%:z",
+            Self::RFC3339Nanos => "%Y-%m-%dT%H:%M:%S%.9f%:z",
+            Self::UnixSecs => "%s",
+            Self::UnixMillis => "%s%3f",
+            Self::UnixNanos => "%s%9f",
+            Self::Elapsed | Self::Delta | Self::None => "",
             Self::Standard => "%Y-%m-%d %H:%M:%S",
             Self::Simple => "%Y%m%d.%H%M%S",
             Self::TimeOnly => "%H:%M:%S",
@@ -37,6 +87,184 @@ impl TSFormat {
     }
 }
 
+/// Displays the time since logger setup: "+12.345s"
+pub(crate) struct Elapsed(pub(crate) Duration);
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{}.{:03}s", self.0.as_secs(), self.0.subsec_millis())
+    }
+}
+
+/// Displays the time since the previous record in the largest whole unit:
+/// "Δ250µs", "Δ3ms" or "Δ1.250s"
+pub(crate) struct Delta(pub(crate) Duration);
+
+impl fmt::Display for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let delta = self.0;
+        if delta.as_secs() > 0 {
+            write!(f, "Δ{}.{:03}s", delta.as_secs(), delta.subsec_millis())
+        } else if delta.subsec_millis() > 0 {
+            write!(f, "Δ{}ms", delta.subsec_millis())
+        } else {
+            write!(f, "Δ{}µs", delta.subsec_micros())
+        }
+    }
+}
+
+/// Timezone timestamps are rendered in.
+///
+/// # Examples
+///
+/// ```
+/// use twyg::{OptsBuilder, Timezone};
+///
+/// let opts = OptsBuilder::new()
+///     .timezone(Timezone::Fixed("+05:30".to_string()))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Timezone {
+    /// The host's local timezone
+    #[default]
+    Local,
+
+    /// UTC
+    Utc,
+
+    /// A fixed offset from UTC: "+05:30", "-08:00"
+    Fixed(String),
+
+    /// An IANA timezone name: "Europe/Berlin" (requires the `tz` feature)
+    Named(String),
+}
+
+impl Timezone {
+    /// Resolves the timezone, validating offsets and names.
+    pub(crate) fn resolve(&self) -> Result<Zone> {
+        match self {
+            Self::Local => Ok(Zone::Local),
+            Self::Utc => Ok(Zone::Utc),
+            Self::Fixed(offset) => offset.parse().map(Zone::Fixed).map_err(|_| {
+                TwygError::ConfigError(format!("invalid timezone offset: {}", offset))
+            }),
+            #[cfg(feature = "tz")]
+            Self::Named(name) => name
+                .parse()
+                .map(Zone::Named)
+                .map_err(|_| TwygError::ConfigError(format!("unknown timezone: {}", name))),
+            #[cfg(not(feature = "tz"))]
+            Self::Named(name) => Err(TwygError::ConfigError(format!(
+                "timezone name '{}' requires the `tz` feature",
+                name
+            ))),
+        }
+    }
+}
+
+/// A resolved [`Timezone`], ready to render timestamps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Zone {
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+    #[cfg(feature = "tz")]
+    Named(chrono_tz::Tz),
+}
+
+impl Zone {
+    /// Displays an instant in this timezone.
+    pub(crate) fn display<'a>(
+        &'a self,
+        now: DateTime<Utc>,
+        format: &'a StrftimeFormat,
+    ) -> ZonedTime<'a> {
+        ZonedTime {
+            zone: self,
+            now,
+            format,
+        }
+    }
+
+    /// Formats an instant in this timezone.
+    #[cfg(test)]
+    pub(crate) fn format(&self, now: DateTime<Utc>, format: &StrftimeFormat) -> String {
+        self.display(now, format).to_string()
+    }
+}
+
+/// An instant rendered in a [`Zone`] without intermediate allocation.
+pub(crate) struct ZonedTime<'a> {
+    zone: &'a Zone,
+    now: DateTime<Utc>,
+    format: &'a StrftimeFormat,
+}
+
+impl fmt::Display for ZonedTime<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let items = self.format.items.iter();
+        match self.zone {
+            Zone::Local => self
+                .now
+                .with_timezone(&Local)
+                .format_with_items(items)
+                .fmt(f),
+            Zone::Utc => self.now.format_with_items(items).fmt(f),
+            Zone::Fixed(offset) => self
+                .now
+                .with_timezone(offset)
+                .format_with_items(items)
+                .fmt(f),
+            #[cfg(feature = "tz")]
+            Zone::Named(tz) => self.now.with_timezone(tz).format_with_items(items).fmt(f),
+        }
+    }
+}
+
+/// A chrono format string parsed once, so records don't re-parse it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct StrftimeFormat {
+    items: Vec<Item<'static>>,
+    subsecond: bool,
+}
+
+impl StrftimeFormat {
+    /// Parses a format string. Invalid specifiers, which `OptsBuilder::build`
+    /// rejects, are rendered literally.
+    pub(crate) fn new(format: &str) -> Self {
+        let items = StrftimeItems::new(format)
+            .parse_to_owned()
+            .or_else(|_| StrftimeItems::new_lenient(format).parse_to_owned())
+            .unwrap_or_default();
+        let subsecond = items.iter().any(|item| {
+            matches!(
+                item,
+                Item::Numeric(Numeric::Nanosecond, _)
+                    | Item::Fixed(
+                        Fixed::Nanosecond
+                            | Fixed::Nanosecond3
+                            | Fixed::Nanosecond6
+                            | Fixed::Nanosecond9
+                            | Fixed::RFC3339
+                            | Fixed::Internal(_)
+                    )
+            )
+        });
+        StrftimeFormat { items, subsecond }
+    }
+
+    /// Returns a key that is equal for instants rendering the same text, or
+    /// `None` if every instant may render differently.
+    ///
+    /// Formats without sub-second fields render the same text throughout a
+    /// second, so the key is the Unix second.
+    pub(crate) fn cache_key(&self, now: DateTime<Utc>) -> Option<i64> {
+        (!self.subsecond).then(|| now.timestamp())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,11 +272,88 @@ mod tests {
     #[test]
     fn test_tsformat_to_format_string() {
         assert_eq!(TSFormat::RFC3339.to_format_string(), "%Y-%m-%dT%H:%M:%S%z");
+        assert_eq!(
+            TSFormat::RFC3339Utc.to_format_string(),
+            "%Y-%m-%dT%H:%M:%SZ"
+        );
         assert_eq!(TSFormat::Standard.to_format_string(), "%Y-%m-%d %H:%M:%S");
         assert_eq!(TSFormat::Simple.to_format_string(), "%Y%m%d.%H%M%S");
         assert_eq!(TSFormat::TimeOnly.to_format_string(), "%H:%M:%S");
     }
 
+    #[test]
+    fn test_tsformat_precision_and_epoch_presets() {
+        let instant = DateTime::parse_from_rfc3339("2024-03-10T12:00:00.123456789Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let zone = Timezone::Fixed("+05:30".to_string()).resolve().unwrap();
+        let render = |format: TSFormat| {
+            zone.format(instant, &StrftimeFormat::new(format.to_format_string()))
+        };
+
+        assert_eq!(
+            render(TSFormat::RFC3339Millis),
+            "2024-03-10T17:30:00.123+05:30"
+        );
+        assert_eq!(
+            render(TSFormat::RFC3339Micros),
+            "2024-03-10T17:30:00.123456+05:30"
+        );
+        assert_eq!(
+            render(TSFormat::RFC3339Nanos),
+            "2024-03-10T17:30:00.123456789+05:30"
+        );
+
+        // Epoch values do not depend on the timezone
+        assert_eq!(render(TSFormat::UnixSecs), "1710072000");
+        assert_eq!(render(TSFormat::UnixMillis), "1710072000123");
+        assert_eq!(render(TSFormat::UnixNanos), "1710072000123456789");
+        assert_eq!(TSFormat::None.to_format_string(), "");
+    }
+
+    #[test]
+    fn test_elapsed_display() {
+        assert_eq!(Elapsed(Duration::ZERO).to_string(), "+0.000s");
+        assert_eq!(
+            Elapsed(Duration::from_millis(12_345)).to_string(),
+            "+12.345s"
+        );
+        assert_eq!(Elapsed(Duration::from_micros(1_999)).to_string(), "+0.001s");
+    }
+
+    #[test]
+    fn test_delta_display() {
+        assert_eq!(Delta(Duration::ZERO).to_string(), "Δ0µs");
+        assert_eq!(Delta(Duration::from_micros(250)).to_string(), "Δ250µs");
+        assert_eq!(Delta(Duration::from_micros(3_400)).to_string(), "Δ3ms");
+        assert_eq!(Delta(Duration::from_millis(1_250)).to_string(), "Δ1.250s");
+    }
+
+    #[test]
+    fn test_tsformat_serialize_deserialize() {
+        for format in [
+            TSFormat::RFC3339,
+            TSFormat::RFC3339Utc,
+            TSFormat::RFC3339Millis,
+            TSFormat::RFC3339Micros,
+            TSFormat::RFC3339Nanos,
+            TSFormat::Standard,
+            TSFormat::Simple,
+            TSFormat::TimeOnly,
+            TSFormat::UnixSecs,
+            TSFormat::UnixMillis,
+            TSFormat::UnixNanos,
+            TSFormat::Elapsed,
+            TSFormat::Delta,
+            TSFormat::None,
+            TSFormat::Custom("%H:%M".to_string()),
+        ] {
+            let serialized = serde_json::to_string(&format).unwrap();
+            let deserialized: TSFormat = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(format, deserialized);
+        }
+    }
+
     #[test]
     fn test_tsformat_custom() {
         let custom = TSFormat::Custom("%H:%M".to_string());
@@ -60,4 +365,120 @@ mod tests {
         let default = TSFormat::default();
         assert_eq!(default, TSFormat::Standard);
     }
+
+    fn instant() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_strftime_format_cache_key() {
+        let now = instant();
+        let later = now + Duration::from_millis(400);
+
+        let seconds = StrftimeFormat::new(TSFormat::Standard.to_format_string());
+        assert_eq!(seconds.cache_key(now), Some(now.timestamp()));
+        assert_eq!(seconds.cache_key(now), seconds.cache_key(later));
+        let next = now + Duration::from_secs(1);
+        assert_ne!(seconds.cache_key(now), seconds.cache_key(next));
+
+        for format in [
+            TSFormat::RFC3339Millis,
+            TSFormat::UnixMillis,
+            TSFormat::UnixNanos,
+            TSFormat::Custom("%H:%M:%S%.f".to_string()),
+            TSFormat::Custom("%+".to_string()),
+        ] {
+            let format = StrftimeFormat::new(format.to_format_string());
+            assert_eq!(format.cache_key(now), None);
+        }
+    }
+
+    #[test]
+    fn test_strftime_format_invalid_is_lenient() {
+        let format = StrftimeFormat::new("%Q %H");
+        let zone = Timezone::Utc.resolve().unwrap();
+        assert_eq!(zone.format(instant(), &format), "%Q 12");
+    }
+
+    #[test]
+    fn test_timezone_default() {
+        assert_eq!(Timezone::default(), Timezone::Local);
+    }
+
+    #[test]
+    fn test_timezone_utc() {
+        let zone = Timezone::Utc.resolve().unwrap();
+        assert_eq!(
+            zone.format(
+                instant(),
+                &StrftimeFormat::new(TSFormat::RFC3339Utc.to_format_string())
+            ),
+            "2024-03-10T12:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_timezone_fixed() {
+        let zone = Timezone::Fixed("+05:30".to_string()).resolve().unwrap();
+        assert_eq!(
+            zone.format(
+                instant(),
+                &StrftimeFormat::new(TSFormat::RFC3339.to_format_string())
+            ),
+            "2024-03-10T17:30:00+0530"
+        );
+
+        let zone = Timezone::Fixed("-08:00".to_string()).resolve().unwrap();
+        assert_eq!(
+            zone.format(instant(), &StrftimeFormat::new("%H:%M")),
+            "04:00"
+        );
+    }
+
+    #[test]
+    fn test_timezone_fixed_invalid() {
+        for offset in ["", "05:30", "+25:00", "Europe/Berlin"] {
+            let result = Timezone::Fixed(offset.to_string()).resolve();
+            assert!(
+                matches!(result, Err(TwygError::ConfigError(_))),
+                "{offset:?} should be rejected"
+            );
+        }
+    }
+
+    #[cfg(feature = "tz")]
+    #[test]
+    fn test_timezone_named() {
+        let zone = Timezone::Named("Asia/Tokyo".to_string()).resolve().unwrap();
+        assert_eq!(
+            zone.format(instant(), &StrftimeFormat::new("%H:%M")),
+            "21:00"
+        );
+
+        let result = Timezone::Named("Mars/Olympus".to_string()).resolve();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+    }
+
+    #[cfg(not(feature = "tz"))]
+    #[test]
+    fn test_timezone_named_requires_feature() {
+        let result = Timezone::Named("Asia/Tokyo".to_string()).resolve();
+        assert!(matches!(result, Err(TwygError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_timezone_serialize_deserialize() {
+        for tz in [
+            Timezone::Local,
+            Timezone::Utc,
+            Timezone::Fixed("+05:30".to_string()),
+            Timezone::Named("Europe/Berlin".to_string()),
+        ] {
+            let serialized = serde_json::to_string(&tz).unwrap();
+            let deserialized: Timezone = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(tz, deserialized);
+        }
+    }
 }
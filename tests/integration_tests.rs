@@ -10,6 +10,7 @@ fn test_setup_with_defaults() {
     let opts = OptsBuilder::new()
         .level(LogLevel::Trace)
         .report_caller(true)
+        .report_column(true)
         .coloured(false)
         .build()
         .unwrap();
@@ -27,6 +28,16 @@ fn test_setup_with_defaults() {
     trace!("Message with {} formatting", "args");
     debug!("Number: {}, String: {}", 42, "test");
     info!("Boolean: {}, Float: {:.2}", true, 12.34);
+
+    // twyg's own macros record the caller column alongside file and line
+    twyg::trace!("Trace via twyg macro");
+    twyg::debug!(target: "custom_target", "Debug with {} target", "custom");
+    twyg::info!(user = "alice", id = 42; "Info with key-values");
+    twyg::warn!(target: "custom_target", attempt = 3; "Warn with target and key-values");
+    twyg::error!("Error via twyg macro: {}", "details");
+    let err = std::io::Error::other("connection reset");
+    twyg::error!(error:err = err; "Error with an error value");
+    twyg::log!(log::Level::Info, "Generic log macro");
 }
 
 // The remaining tests verify that Logger can be created and configured